@@ -2,7 +2,7 @@ use crate::{lobby::Lobby, region::RegionInfo, summoner::Summoner};
 use serde_json::json;
 
 pub async fn send_analytics_event(team: &Lobby, summoner: &Summoner, region: &RegionInfo) {
-    let summoner_name = format!("{}#{}", summoner.game_name, summoner.tag_line);
+    let summoner_name = summoner.riot_id();
 
     // send analytics event
     let client = reqwest::Client::new();
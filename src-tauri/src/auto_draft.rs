@@ -0,0 +1,267 @@
+use crate::{
+    champ_select::{self, ChampSelectActionType, ChampSelectSession, SessionEvent},
+    config::{AutoDraft, ANY_POSITION},
+    lcu_api::LcuApi,
+    websocket::LcuEvent,
+};
+use std::{collections::HashSet, time::Duration};
+use tokio::{sync::broadcast, time::Instant};
+
+/// What to do with the local player's current pick or ban.
+#[derive(Debug, PartialEq, Eq)]
+struct DraftStep {
+    action_id: u64,
+    champion_id: u32,
+    /// How long to hover before locking in; zero locks in now.
+    lock_in: Duration,
+}
+
+/// Hovers and locks in bans and picks from the configured priority lists
+/// whenever the local player is on the clock. Runs until the session stream
+/// ends; the phase supervisor cancels it when Champ Select ends.
+pub async fn run(
+    lcu: &impl LcuApi,
+    draft: &AutoDraft,
+    sessions: &mut broadcast::Receiver<LcuEvent>,
+) {
+    log_info!(
+        "Auto draft started; auto_ban={}, auto_pick={}, lock_at_seconds_left={}",
+        draft.auto_ban,
+        draft.auto_pick,
+        draft.lock_at_seconds_left
+    );
+    let mut session = lcu.champ_select_session().await.ok();
+    let mut received_at = Instant::now();
+    let mut last_patch = None;
+
+    loop {
+        let step = session
+            .as_ref()
+            .and_then(|session| plan(session, draft, received_at.elapsed()));
+        let mut lock_at = None;
+
+        if let Some(step) = step {
+            let completed = step.lock_in.is_zero();
+            let patch = (step.action_id, step.champion_id, completed);
+            if last_patch != Some(patch) {
+                log_info!(
+                    "{} champion {} for action {}",
+                    if completed { "Locking in" } else { "Hovering" },
+                    step.champion_id,
+                    step.action_id
+                );
+                match lcu
+                    .patch_champ_select_action(step.action_id, step.champion_id, completed)
+                    .await
+                {
+                    Ok(()) => last_patch = Some(patch),
+                    Err(error) => log_warn!("Auto draft action failed: {error}"),
+                }
+            }
+            if !completed {
+                lock_at = Some(Instant::now() + step.lock_in);
+            }
+        }
+
+        tokio::select! {
+            event = champ_select::next_session(sessions) => {
+                let Some(SessionEvent::Update(update)) = event else {
+                    return;
+                };
+                session = Some(*update);
+                received_at = Instant::now();
+            }
+            _ = tokio::time::sleep_until(lock_at.unwrap_or_else(Instant::now)),
+                if lock_at.is_some() => {}
+        }
+    }
+}
+
+/// Picks the best available champion for the local player's in-progress
+/// action. `elapsed` is the time since `session` was received.
+fn plan(session: &ChampSelectSession, draft: &AutoDraft, elapsed: Duration) -> Option<DraftStep> {
+    let action = session.actions.iter().flatten().find(|action| {
+        action.actor_cell_id == session.local_player_cell_id
+            && action.is_in_progress
+            && !action.completed
+    })?;
+    let priorities = match action.kind {
+        ChampSelectActionType::Ban if draft.auto_ban => &draft.bans,
+        ChampSelectActionType::Pick if draft.auto_pick => &draft.picks,
+        _ => return None,
+    };
+
+    let champions = priorities
+        .get(session.local_position())
+        .filter(|champions| !champions.is_empty())
+        .or_else(|| priorities.get(ANY_POSITION))?;
+
+    let unavailable = unavailable_champions(session);
+    let champion_id = champions
+        .iter()
+        .copied()
+        .find(|champion_id| !unavailable.contains(champion_id))?;
+
+    let time_left =
+        Duration::from_millis(session.timer.adjusted_time_left_in_phase).saturating_sub(elapsed);
+    let lock_in = time_left.saturating_sub(Duration::from_secs(draft.lock_at_seconds_left.into()));
+
+    Some(DraftStep {
+        action_id: action.id,
+        champion_id,
+        lock_in,
+    })
+}
+
+/// Champions that are banned, picked, or claimed by a teammate.
+fn unavailable_champions(session: &ChampSelectSession) -> HashSet<u32> {
+    let completed = session
+        .actions
+        .iter()
+        .flatten()
+        .filter(|action| action.completed)
+        .map(|action| action.champion_id);
+    let teammates = session
+        .my_team
+        .iter()
+        .filter(|member| i64::from(member.cell_id) != session.local_player_cell_id)
+        .flat_map(|member| [member.champion_id, member.champion_pick_intent]);
+    let enemies = session.their_team.iter().map(|member| member.champion_id);
+
+    session
+        .bans
+        .my_team_bans
+        .iter()
+        .chain(&session.bans.their_team_bans)
+        .copied()
+        .chain(completed)
+        .chain(teammates)
+        .chain(enemies)
+        .filter(|champion_id| *champion_id != 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{event_bus::EventBus, lcu_api::fake::FakeLcuApi};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    /// Local player in cell 2 playing jungle, with the given action on the
+    /// clock and 20 seconds left.
+    fn session(action_type: &str, actor_cell_id: i64) -> ChampSelectSession {
+        serde_json::from_value(session_json(action_type, actor_cell_id)).unwrap()
+    }
+
+    fn session_json(action_type: &str, actor_cell_id: i64) -> Value {
+        json!({
+            "gameId": 1,
+            "localPlayerCellId": 2,
+            "timer": { "adjustedTimeLeftInPhase": 20_000, "phase": "BAN_PICK" },
+            "myTeam": [
+                { "cellId": 0, "assignedPosition": "top", "championPickIntent": 238 },
+                { "cellId": 1, "assignedPosition": "middle", "championId": 103 },
+                { "cellId": 2, "assignedPosition": "jungle" }
+            ],
+            "theirTeam": [{ "cellId": 5, "championId": 121 }],
+            "bans": { "myTeamBans": [], "theirTeamBans": [266] },
+            "actions": [
+                [{ "id": 1, "actorCellId": 1, "championId": 64, "completed": true, "type": "ban" }],
+                [{
+                    "id": 7,
+                    "actorCellId": actor_cell_id,
+                    "isInProgress": true,
+                    "type": action_type
+                }]
+            ]
+        })
+    }
+
+    fn draft() -> AutoDraft {
+        AutoDraft {
+            auto_ban: true,
+            auto_pick: true,
+            lock_at_seconds_left: 5,
+            bans: [("jungle".to_string(), vec![64, 266, 238, 157])].into(),
+            picks: [(ANY_POSITION.to_string(), vec![103, 121, 64, 76])].into(),
+        }
+    }
+
+    #[test]
+    fn bans_the_first_champion_nobody_banned_or_claimed() {
+        let step = plan(&session("ban", 2), &draft(), Duration::ZERO).unwrap();
+
+        assert_eq!(
+            step,
+            DraftStep {
+                action_id: 7,
+                champion_id: 157,
+                lock_in: Duration::from_secs(15),
+            }
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_any_position_list() {
+        let step = plan(&session("pick", 2), &draft(), Duration::from_secs(16)).unwrap();
+
+        assert_eq!(step.champion_id, 76);
+        assert_eq!(step.lock_in, Duration::ZERO);
+    }
+
+    #[test]
+    fn only_acts_on_the_local_players_enabled_action() {
+        assert_eq!(plan(&session("ban", 3), &draft(), Duration::ZERO), None);
+        assert_eq!(plan(&session("vote", 2), &draft(), Duration::ZERO), None);
+
+        let bans_disabled = AutoDraft {
+            auto_ban: false,
+            ..draft()
+        };
+        assert_eq!(
+            plan(&session("ban", 2), &bans_disabled, Duration::ZERO),
+            None
+        );
+
+        let exhausted = AutoDraft {
+            bans: [("jungle".to_string(), vec![64, 266])].into(),
+            ..draft()
+        };
+        assert_eq!(plan(&session("ban", 2), &exhausted, Duration::ZERO), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hovers_then_locks_in_when_the_timer_runs_low() {
+        let lcu = FakeLcuApi::default();
+        lcu.state().session = Some(session("ban", 2));
+        let bus = Arc::new(EventBus::default());
+        let mut sessions = bus.subscribe(crate::champ_select::SESSION_URI);
+        let task = tokio::spawn({
+            let lcu = lcu.clone();
+            async move { run(&lcu, &draft(), &mut sessions).await }
+        });
+
+        tokio::time::sleep(Duration::from_secs(14)).await;
+        assert_eq!(lcu.state().action_patches, [(7, 157, false)]);
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(
+            lcu.state().action_patches,
+            [(7, 157, false), (7, 157, true)]
+        );
+
+        let mut locked = session_json("ban", 2);
+        locked["actions"][1][0]["completed"] = json!(true);
+        locked["actions"][1][0]["isInProgress"] = json!(false);
+        bus.publish(LcuEvent {
+            uri: crate::champ_select::SESSION_URI.to_string(),
+            event_type: "Update".to_string(),
+            data: locked,
+        });
+        bus.close();
+        task.await.unwrap();
+
+        assert_eq!(lcu.state().action_patches.len(), 2);
+    }
+}
@@ -0,0 +1,43 @@
+//! Runs the mock League Client so Reveal can be started against it.
+//!
+//! Usage: `cargo run --features mock-lcu --bin mock-lcu -- <timeline.json> [port]`
+//! and launch Reveal with the printed `REVEAL_LCU_PORT`/`REVEAL_LCU_TOKEN`.
+
+#[path = "../mock_lcu.rs"]
+mod mock_lcu;
+
+use anyhow::{Context, Result};
+use mock_lcu::{MockLcu, Timeline, DEFAULT_AUTH_TOKEN};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let timeline_path = args
+        .next()
+        .context("usage: mock-lcu <timeline.json> [port]")?;
+    let port = match args.next() {
+        Some(port) => port.parse().context("port must be a number")?,
+        None => 0,
+    };
+
+    let timeline = Timeline::from_json(
+        &std::fs::read_to_string(&timeline_path)
+            .with_context(|| format!("failed to read {timeline_path}"))?,
+    )?;
+    let mock = MockLcu::bind(port, DEFAULT_AUTH_TOKEN).await?;
+
+    println!("Mock LCU listening on 127.0.0.1:{}", mock.port());
+    println!("REVEAL_LCU_PORT={}", mock.port());
+    println!("REVEAL_LCU_TOKEN={}", mock.auth_token());
+    println!("Press Enter to replay the timeline, Ctrl+C to exit");
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while lines.next_line().await?.is_some() {
+        println!("Replaying {} steps", timeline.steps.len());
+        mock.replay(&timeline).await;
+        println!("Timeline finished");
+    }
+
+    Ok(())
+}
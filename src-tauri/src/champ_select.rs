@@ -1,148 +1,561 @@
 use crate::{
-    analytics, config::Config, lobby, region::RegionInfo, summoner, utils::display_champ_select,
+    analytics,
+    config::Config,
+    emitter::Emitter,
+    event_bus::{self, EventBus, PhaseReceiver},
+    gameflow::GameflowPhase,
+    lcu_api::LcuApi,
+    lobby,
+    lobby_history::LobbyHistory,
+    logging,
+    player_notes::PlayerNotes,
+    region::Region,
+    utils::display_champ_select,
+    websocket::LcuEvent,
 };
-use serde::Deserialize;
-use shaco::rest::RESTClient;
-use std::{
-    collections::HashMap,
-    time::{Duration, Instant},
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Duration};
+use tokio::{
+    sync::broadcast,
+    time::{Instant, MissedTickBehavior},
 };
-use tauri::{AppHandle, Manager};
 
+pub const SESSION_URI: &str = "/lol-champ-select/v1/session";
 const EXPECTED_PARTICIPANT_COUNT: usize = 5;
-const PARTICIPANT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How often the REST endpoints are re-read in case a websocket event was missed.
+const FALLBACK_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChampSelectSession {
     pub game_id: u64,
     pub timer: Timer,
+    /// `-1` when the local player has no cell, e.g. while spectating.
+    #[serde(default = "no_cell")]
+    pub local_player_cell_id: i64,
+    #[serde(default)]
+    pub is_spectating: bool,
     #[serde(default)]
     pub my_team: Vec<ChampSelectTeamMember>,
+    #[serde(default)]
+    pub their_team: Vec<ChampSelectTeamMember>,
+    /// Draft turns in order; every action in a turn happens simultaneously.
+    #[serde(default)]
+    pub actions: Vec<Vec<ChampSelectAction>>,
+    #[serde(default)]
+    pub bans: Bans,
+    #[serde(default)]
+    pub bench_enabled: bool,
+    #[serde(default)]
+    pub bench_champions: Vec<BenchChampion>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChampSelectTeamMember {
     #[serde(default)]
     pub assigned_position: String,
     pub cell_id: u32,
+    #[serde(default)]
     pub pick_turn: u32,
+    /// Empty for enemies, whose identities the client hides.
+    #[serde(default)]
     pub puuid: String,
+    /// Locked or currently selected champion, `0` if none.
+    #[serde(default)]
+    pub champion_id: u32,
+    /// Champion declared during planning, `0` if none.
+    #[serde(default)]
+    pub champion_pick_intent: u32,
+    #[serde(default)]
+    pub spell1_id: u64,
+    #[serde(default)]
+    pub spell2_id: u64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampSelectAction {
+    pub id: u64,
+    /// `-1` for actions no player performs, such as revealing bans.
+    pub actor_cell_id: i64,
+    #[serde(default)]
+    pub champion_id: u32,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default)]
+    pub is_in_progress: bool,
+    #[serde(default)]
+    pub is_ally_action: bool,
+    #[serde(rename = "type")]
+    pub kind: ChampSelectActionType,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChampSelectActionType {
+    Pick,
+    Ban,
+    TenBansReveal,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bans {
+    #[serde(default)]
+    pub my_team_bans: Vec<u32>,
+    #[serde(default)]
+    pub their_team_bans: Vec<u32>,
+    #[serde(default)]
+    pub num_bans: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchChampion {
+    pub champion_id: u32,
+    #[serde(default)]
+    pub is_priority: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Timer {
     pub adjusted_time_left_in_phase: u64,
     pub phase: String,
 }
 
+impl ChampSelectSession {
+    fn local_member(&self) -> Option<&ChampSelectTeamMember> {
+        self.my_team
+            .iter()
+            .find(|member| i64::from(member.cell_id) == self.local_player_cell_id)
+    }
+
+    /// The local player's assigned position, empty in modes without one.
+    pub fn local_position(&self) -> &str {
+        self.local_member()
+            .map(|member| member.assigned_position.as_str())
+            .unwrap_or_default()
+    }
+
+    /// The local player's puuid, empty while spectating.
+    pub fn local_puuid(&self) -> &str {
+        self.local_member()
+            .map(|member| member.puuid.as_str())
+            .unwrap_or_default()
+    }
+
+    /// The champion the local player has locked in, if any.
+    pub fn locked_champion(&self) -> Option<u32> {
+        self.actions
+            .iter()
+            .flatten()
+            .find(|action| {
+                action.actor_cell_id == self.local_player_cell_id
+                    && action.kind == ChampSelectActionType::Pick
+                    && action.completed
+                    && action.champion_id != 0
+            })
+            .map(|action| action.champion_id)
+    }
+}
+
+fn no_cell() -> i64 {
+    -1
+}
+
+/// Streams the Champ Select session to the frontend for the lifetime of the
+/// connection, and registers the resources the participant tracker follows.
+pub fn register(bus: &EventBus, emitter: impl Emitter + 'static) {
+    let mut sessions = bus.subscribe(SESSION_URI);
+    bus.register_uri(lobby::CHAT_PARTICIPANTS_URI);
+
+    tauri::async_runtime::spawn(logging::inherit(async move {
+        while let Some(event) = event_bus::next(&mut sessions).await {
+            // The session is deleted when Champ Select ends
+            let session = if event.event_type == "Delete" {
+                None
+            } else {
+                match serde_json::from_value::<ChampSelectSession>(event.data) {
+                    Ok(session) => Some(session),
+                    Err(error) => {
+                        log_warn!("Failed to parse Champ Select session: {error}");
+                        continue;
+                    }
+                }
+            };
+
+            if let Err(error) = emitter.emit_event("champ_select_session_update", session) {
+                log_error!("Failed to emit Champ Select session: {error}");
+            }
+        }
+    }));
+}
+
+/// A Champ Select session event from [`next_session`].
+pub enum SessionEvent {
+    Update(Box<ChampSelectSession>),
+    /// The session was deleted; Champ Select ended.
+    Delete,
+}
+
+/// Waits for the next session event on a [`SESSION_URI`] subscription, or
+/// `None` once the bus closes. Sessions that do not parse are skipped; the
+/// session stream started by [`register`] already reports them.
+pub async fn next_session(sessions: &mut broadcast::Receiver<LcuEvent>) -> Option<SessionEvent> {
+    loop {
+        let event = event_bus::next(sessions).await?;
+        if event.event_type == "Delete" {
+            return Some(SessionEvent::Delete);
+        }
+        if let Ok(session) = serde_json::from_value(event.data) {
+            return Some(SessionEvent::Update(session));
+        }
+    }
+}
+
+/// Where revealed lobbies are recorded and what they are annotated with.
+#[derive(Clone)]
+pub struct PlayerRecords {
+    pub history: LobbyHistory,
+    pub notes: PlayerNotes,
+}
+
+/// Live Champ Select data pushed from the client websockets.
+enum ChampSelectUpdate {
+    Participants(lobby::Lobby),
+    Session(ChampSelectSession),
+}
+
+/// Websocket feeds the participant tracker follows during one Champ Select.
+pub struct ChampSelectUpdates {
+    sessions: broadcast::Receiver<LcuEvent>,
+    participants: broadcast::Receiver<LcuEvent>,
+    phases: PhaseReceiver,
+}
+
+impl ChampSelectUpdates {
+    pub fn subscribe(bus: &EventBus) -> Self {
+        Self {
+            sessions: bus.subscribe(SESSION_URI),
+            participants: bus.subscribe(lobby::CHAT_PARTICIPANTS_URI),
+            phases: bus.subscribe_phases(&[GameflowPhase::ChampSelect]),
+        }
+    }
+
+    /// Waits for the next update, or `None` once Champ Select ends.
+    async fn next(&mut self) -> Option<ChampSelectUpdate> {
+        loop {
+            tokio::select! {
+                event = event_bus::next(&mut self.sessions) => {
+                    let event = event?;
+                    if event.event_type == "Delete" {
+                        log_trace!("Ignoring a Champ Select session delete event");
+                        continue;
+                    }
+                    match serde_json::from_value::<ChampSelectSession>(event.data) {
+                        Ok(session) => {
+                            log_trace!(
+                                "Champ Select session {} event in phase {}",
+                                event.event_type,
+                                session.timer.phase
+                            );
+                            return Some(ChampSelectUpdate::Session(session));
+                        }
+                        Err(error) => log_warn!("Failed to parse Champ Select session: {error}"),
+                    }
+                }
+                event = event_bus::next(&mut self.participants) => {
+                    let event = event?;
+                    if event.event_type == "Delete" {
+                        log_trace!("Ignoring a chat participants delete event");
+                        continue;
+                    }
+                    match serde_json::from_value(event.data) {
+                        Ok(team) => {
+                            let team = lobby::champ_select_participants(team);
+                            log_trace!(
+                                "Chat participants {} event with {} teammates",
+                                event.event_type,
+                                team.participants.len()
+                            );
+                            return Some(ChampSelectUpdate::Participants(team));
+                        }
+                        Err(error) => log_warn!("Failed to parse chat participants: {error}"),
+                    }
+                }
+                change = self.phases.next() => {
+                    if change?.left(&GameflowPhase::ChampSelect) {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Tracks Champ Select until the team is revealed, or until `updates` reports
+/// that Champ Select ended.
 pub async fn handle_champ_select_start(
-    app_client: &RESTClient,
-    remoting_client: &RESTClient,
+    lcu: &impl LcuApi,
     config: &Config,
-    app_handle: &AppHandle,
+    emitter: &impl Emitter,
+    records: &PlayerRecords,
+    mut updates: ChampSelectUpdates,
 ) {
     log_info!(
-        "Champ Select participant polling started; auto_open={}, max_wait_seconds={}, provider={}",
+        "Champ Select participant tracking started; auto_open={}, max_wait_seconds={}, provider={}",
         config.auto_open,
         config.auto_open_delay_seconds,
         config.multi_provider
     );
-    let region_info: RegionInfo = match app_client
-        .get("/riotclient/region-locale".to_string())
-        .await
-        .map_err(anyhow::Error::from)
-        .and_then(|value| serde_json::from_value(value).map_err(anyhow::Error::from))
-    {
+    let region_info = match lcu.region_locale().await {
         Ok(region_info) => region_info,
         Err(error) => {
             log_error!("Failed to read League region: {error}");
             return;
         }
     };
+    let region = match Region::try_from(&region_info) {
+        Ok(region) => region,
+        Err(error) => {
+            log_error!("{error}");
+            return;
+        }
+    };
+    match region.routing() {
+        Some(routing) => log_info!(
+            "League region is {region} (platform {}, {} routing)",
+            region.platform_id(),
+            routing.as_str()
+        ),
+        None => log_warn!("League region {region} is not known; using it as is for links"),
+    }
+
+    let queue_id = match lcu.gameflow_session().await {
+        Ok(gameflow) => gameflow.game_data.queue.id,
+        Err(error) => {
+            log_warn!("Failed to read the queue for lobby history: {error}");
+            -1
+        }
+    };
 
-    let region = match region_info.web_region.as_str() {
-        "SG2" => "SG",
-        _ => &region_info.web_region,
+    let Some(team) = track_participants(
+        lcu,
+        config,
+        emitter,
+        records,
+        &region,
+        queue_id,
+        &mut updates,
+    )
+    .await
+    else {
+        return;
     };
 
-    let mut auto_opened = false;
-    let mut last_participant_count = 0;
-    let mut last_emitted_team: Option<lobby::Lobby> = None;
-    let mut first_participant_seen_at: Option<Instant> = None;
+    // Only send analytics once we have all 5 teammates
+    match lcu.current_summoner().await {
+        Ok(summoner) => {
+            analytics::send_analytics_event(&team, &summoner, &region_info).await;
+        }
+        Err(error) => {
+            log_warn!("Failed to read current summoner for analytics: {error}");
+        }
+    }
+}
+
+/// Follows websocket updates until all teammates are revealed, returning the
+/// complete team, or `None` if Champ Select ends first. The REST endpoints are
+/// only polled as a fallback in case an event is missed. Every revealed team
+/// is annotated from `records` and recorded in the lobby history once its
+/// game is known.
+async fn track_participants(
+    lcu: &impl LcuApi,
+    config: &Config,
+    emitter: &impl Emitter,
+    records: &PlayerRecords,
+    region: &Region,
+    queue_id: i64,
+    updates: &mut ChampSelectUpdates,
+) -> Option<lobby::Lobby> {
+    let mut progress = RevealProgress::default();
+    let mut recorded_team: Option<lobby::Lobby> = None;
+    let mut participants: Option<lobby::Lobby> = None;
+    let mut session: Option<ChampSelectSession> = None;
     let mut session_warning_logged = false;
+    // The first tick completes immediately and fetches the initial state.
+    let mut fallback_poll = tokio::time::interval(FALLBACK_POLL_INTERVAL);
+    fallback_poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
-    // Poll until we have all 5 teammates or champ select ends
     loop {
-        // Champ select sanity check
-        let gameflow_state = remoting_client
-            .get("/lol-gameflow/v1/gameflow-phase".to_string())
-            .await;
+        let auto_open_at = progress.auto_open_deadline(config);
+
+        tokio::select! {
+            update = updates.next() => match update {
+                Some(ChampSelectUpdate::Participants(team)) => participants = Some(team),
+                Some(ChampSelectUpdate::Session(update)) => session = Some(update),
+                None => {
+                    log_info!("Champ Select ended; stopping participant tracking");
+                    return None;
+                }
+            },
+            _ = fallback_poll.tick() => {
+                log_debug!("Polling Champ Select in case an event was missed");
+                // Champ select sanity check
+                match lcu.gameflow_phase().await {
+                    Ok(GameflowPhase::ChampSelect) => {}
+                    Ok(_) => {
+                        log_info!("Champ Select ended; stopping participant tracking");
+                        return None;
+                    }
+                    Err(_) => {
+                        // Invalid gameflow state - assume we're not in champ select
+                        log_warn!(
+                            "Could not confirm the current gameflow state; stopping Champ Select tracking"
+                        );
+                        return None;
+                    }
+                }
+
+                // This will return an error if we aren't connected to chat
+                match lobby::get_lobby_info(lcu).await {
+                    Ok(team) => participants = Some(team),
+                    Err(error) => {
+                        log_warn!("Lobby participants are not available yet; retrying: {error}");
+                    }
+                }
 
-        if let Ok(state) = gameflow_state {
-            let state_str = state.to_string().replace('\"', "");
-            if state_str != "ChampSelect" {
-                log_info!("Champ Select ended; stopping participant polling");
-                break;
+                match lcu.champ_select_session().await {
+                    Ok(update) => {
+                        session_warning_logged = false;
+                        session = Some(update);
+                    }
+                    Err(error) => {
+                        if !session_warning_logged {
+                            log_warn!(
+                                "Champ Select slot information is not available yet; names will still be revealed: {error}"
+                            );
+                            session_warning_logged = true;
+                        }
+                    }
+                }
+            },
+            // Wakes the loop so an incomplete team still opens on time.
+            _ = tokio::time::sleep_until(auto_open_at.unwrap_or_else(Instant::now)),
+                if auto_open_at.is_some() => {
+                log_debug!("Auto-open wait elapsed; opening the teammates revealed so far");
             }
-        } else {
-            // Invalid gameflow state - assume we're not in champ select
-            log_warn!(
-                "Could not confirm the current gameflow state; stopping Champ Select polling"
-            );
-            break;
         }
 
-        // This will return an error if we aren't connected to chat
-        let mut team = match lobby::get_lobby_info(app_client).await {
-            Ok(team) => team,
-            Err(error) => {
-                log_warn!("Lobby participants are not available yet; retrying: {error}");
-                tokio::time::sleep(PARTICIPANT_POLL_INTERVAL).await;
-                continue;
-            }
+        let Some(team) = &participants else {
+            continue;
         };
-
-        let mapped_participant_count = match get_champ_select_session(remoting_client).await {
-            Ok(session) => {
-                session_warning_logged = false;
-                apply_champ_select_context(&mut team, &session.my_team)
-            }
-            Err(error) => {
-                if !session_warning_logged {
-                    log_warn!(
-                        "Champ Select slot information is not available yet; names will still be revealed: {error}"
-                    );
-                    session_warning_logged = true;
-                }
-                0
+        let mut team = team.clone();
+        records.notes.annotate(&mut team).await;
+        records
+            .history
+            .annotate_played_with(
+                &mut team,
+                session.as_ref().map(|session| session.game_id),
+                session
+                    .as_ref()
+                    .map(ChampSelectSession::local_puuid)
+                    .unwrap_or_default(),
+            )
+            .await;
+        let complete_team = progress.update(team, session.as_ref(), config, emitter, region);
+
+        if let (Some(session), Some(team)) = (&session, &progress.last_emitted_team) {
+            if recorded_team.as_ref() != Some(team) {
+                let history = &records.history;
+                let game_id = session.game_id;
+                record_reveal(history, emitter, game_id, queue_id, region.name(), team).await;
+                recorded_team = Some(team.clone());
             }
-        };
+        }
+        if complete_team.is_some() {
+            return complete_team;
+        }
+    }
+}
+
+async fn record_reveal(
+    history: &LobbyHistory,
+    emitter: &impl Emitter,
+    game_id: u64,
+    queue_id: i64,
+    region: &str,
+    team: &lobby::Lobby,
+) {
+    if let Err(error) = history.record(game_id, queue_id, region, team).await {
+        log_error!("Failed to record lobby history: {error:#}");
+        return;
+    }
+    if let Err(error) = emitter.emit_event("lobby_history_updated", game_id) {
+        log_error!("Failed to emit lobby history update: {error}");
+    }
+}
+
+/// What has been revealed and opened so far during one Champ Select.
+#[derive(Default)]
+struct RevealProgress {
+    auto_opened: bool,
+    last_participant_count: usize,
+    last_emitted_team: Option<lobby::Lobby>,
+    first_participant_seen_at: Option<Instant>,
+}
+
+impl RevealProgress {
+    /// When the auto-open fallback for an incomplete team fires, if pending.
+    fn auto_open_deadline(&self, config: &Config) -> Option<Instant> {
+        if !config.auto_open || self.auto_opened {
+            return None;
+        }
+
+        self.first_participant_seen_at
+            .map(|seen_at| seen_at + Duration::from_secs(config.auto_open_delay_seconds.into()))
+    }
+
+    /// Emits and auto-opens the latest team, returning it once it is complete.
+    fn update(
+        &mut self,
+        mut team: lobby::Lobby,
+        session: Option<&ChampSelectSession>,
+        config: &Config,
+        emitter: &impl Emitter,
+        region: &Region,
+    ) -> Option<lobby::Lobby> {
+        let mapped_participant_count = session
+            .map(|session| apply_champ_select_context(&mut team, &session.my_team))
+            .unwrap_or(0);
         let participant_count = team.participants.len();
 
-        if participant_count > 0 && first_participant_seen_at.is_none() {
-            first_participant_seen_at = Some(Instant::now());
+        if participant_count > 0 && self.first_participant_seen_at.is_none() {
+            self.first_participant_seen_at = Some(Instant::now());
         }
 
-        if last_emitted_team.as_ref() != Some(&team) {
-            if participant_count > last_participant_count {
+        if self.last_emitted_team.as_ref() != Some(&team) {
+            if participant_count > self.last_participant_count {
                 log_info!(
                     "Champ Select participant count increased to {} from {}",
                     participant_count,
-                    last_participant_count
+                    self.last_participant_count
                 );
-                last_participant_count = participant_count;
+                self.last_participant_count = participant_count;
             }
 
-            if let Err(error) = app_handle.emit_all("champ_select_started", &team) {
+            if let Err(error) = emitter.emit_event("champ_select_started", &team) {
                 log_error!("Failed to emit Champ Select participants: {error}");
             }
-            last_emitted_team = Some(team.clone());
+            self.last_emitted_team = Some(team.clone());
         }
 
-        let auto_open_elapsed = first_participant_seen_at
+        let auto_open_elapsed = self
+            .first_participant_seen_at
             .map(|started_at| started_at.elapsed())
             .unwrap_or_default();
         let auto_open_max_wait = Duration::from_secs(config.auto_open_delay_seconds.into());
@@ -151,12 +564,12 @@ pub async fn handle_champ_select_start(
         // participant list once the configured maximum wait has elapsed.
         if should_auto_open(
             config.auto_open,
-            auto_opened,
+            self.auto_opened,
             participant_count,
             auto_open_elapsed,
             auto_open_max_wait,
         ) {
-            if let Err(error) = display_champ_select(&team, region, &config.multi_provider) {
+            if let Err(error) = display_champ_select(&team, region, config) {
                 log_error!("Automatic multi-search open failed: {error}");
             } else {
                 log_info!(
@@ -166,38 +579,26 @@ pub async fn handle_champ_select_start(
                     auto_open_elapsed
                 );
             }
-            auto_opened = true;
+            self.auto_opened = true;
+        } else if config.auto_open && !self.auto_opened && participant_count > 0 {
+            log_debug!(
+                "Holding the auto-open for {participant_count} of {EXPECTED_PARTICIPANT_COUNT} teammates; {:?} of {:?} waited",
+                auto_open_elapsed,
+                auto_open_max_wait
+            );
         }
 
-        // Only send analytics once we have all 5 teammates
         if participant_count >= EXPECTED_PARTICIPANT_COUNT
             && mapped_participant_count >= participant_count
         {
-            match summoner::get_current_summoner(remoting_client).await {
-                Ok(summoner) => {
-                    analytics::send_analytics_event(&team, &summoner, &region_info).await;
-                }
-                Err(error) => {
-                    log_warn!("Failed to read current summoner for analytics: {error}");
-                }
-            }
-            log_info!("Complete Champ Select team found; participant polling finished");
-            break;
+            log_info!("Complete Champ Select team found; participant tracking finished");
+            return Some(team);
         }
 
-        tokio::time::sleep(PARTICIPANT_POLL_INTERVAL).await;
+        None
     }
 }
 
-async fn get_champ_select_session(
-    remoting_client: &RESTClient,
-) -> anyhow::Result<ChampSelectSession> {
-    let session = remoting_client
-        .get("/lol-champ-select/v1/session".to_string())
-        .await?;
-    Ok(serde_json::from_value(session)?)
-}
-
 fn apply_champ_select_context(
     lobby: &mut lobby::Lobby,
     champ_select_team: &[ChampSelectTeamMember],
@@ -246,6 +647,9 @@ fn should_auto_open(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{emitter::RecordingEmitter, lcu_api::fake::FakeLcuApi};
+    use serde_json::json;
+    use std::sync::Arc;
 
     const MAX_WAIT: Duration = Duration::from_secs(6);
 
@@ -304,6 +708,8 @@ mod tests {
             assigned_position: None,
             cell_id: None,
             pick_turn: None,
+            played_with: None,
+            note: None,
         }
     }
 
@@ -321,12 +727,14 @@ mod tests {
                 cell_id: 4,
                 pick_turn: 1,
                 puuid: "second-puuid".to_string(),
+                ..Default::default()
             },
             ChampSelectTeamMember {
                 assigned_position: "jungle".to_string(),
                 cell_id: 2,
                 pick_turn: 0,
                 puuid: "first-puuid".to_string(),
+                ..Default::default()
             },
         ];
 
@@ -357,4 +765,213 @@ mod tests {
         assert_eq!(team.participants[0].pick_turn, None);
         assert_eq!(team.participants[0].assigned_position, None);
     }
+
+    /// Chat lists teammates in join order, which is not pick order.
+    fn chat_participants(count: usize) -> Vec<lobby::Participant> {
+        (0..count)
+            .rev()
+            .map(|index| participant(&format!("puuid-{index}"), &format!("Player {index}")))
+            .collect()
+    }
+
+    fn fake_champ_select(participant_count: usize) -> FakeLcuApi {
+        let lcu = FakeLcuApi::default();
+        let mut state = lcu.state();
+        state.gameflow_phase = GameflowPhase::ChampSelect;
+        state.web_region = "NA".to_string();
+        state.participants = chat_participants(participant_count);
+        let my_team: Vec<_> = (0..EXPECTED_PARTICIPANT_COUNT)
+            .map(|index| json!({ "cellId": index, "pickTurn": index, "puuid": format!("puuid-{index}") }))
+            .collect();
+        state.session = Some(
+            serde_json::from_value(json!({
+                "gameId": 1,
+                "timer": { "adjustedTimeLeftInPhase": 30_000, "phase": "PLANNING" },
+                "myTeam": my_team
+            }))
+            .unwrap(),
+        );
+        drop(state);
+        lcu
+    }
+
+    fn manual_open_config() -> Config {
+        Config {
+            auto_open: false,
+            ..Config::default()
+        }
+    }
+
+    fn spawn_tracker(
+        lcu: &FakeLcuApi,
+        emitter: &Arc<RecordingEmitter>,
+    ) -> (Arc<EventBus>, tokio::task::JoinHandle<Option<lobby::Lobby>>) {
+        let bus = Arc::new(EventBus::default());
+        bus.publish_phase(GameflowPhase::ChampSelect);
+        let mut updates = ChampSelectUpdates::subscribe(&bus);
+        let task = tokio::spawn({
+            let lcu = lcu.clone();
+            let emitter = emitter.clone();
+            async move {
+                track_participants(
+                    &lcu,
+                    &manual_open_config(),
+                    emitter.as_ref(),
+                    &PlayerRecords {
+                        history: LobbyHistory::temporary(),
+                        notes: PlayerNotes::temporary(),
+                    },
+                    &Region::Na,
+                    420,
+                    &mut updates,
+                )
+                .await
+            }
+        });
+        (bus, task)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reveals_a_complete_team_in_pick_order() {
+        let lcu = fake_champ_select(EXPECTED_PARTICIPANT_COUNT);
+        let emitter = Arc::new(RecordingEmitter::default());
+
+        let (_bus, task) = spawn_tracker(&lcu, &emitter);
+        let team = task.await.unwrap().unwrap();
+
+        let puuids: Vec<_> = team.participants.iter().map(|p| p.puuid.as_str()).collect();
+        assert_eq!(
+            puuids,
+            ["puuid-0", "puuid-1", "puuid-2", "puuid-3", "puuid-4"]
+        );
+        assert_eq!(emitter.payloads("champ_select_started").len(), 1);
+        assert_eq!(emitter.payloads("lobby_history_updated"), [json!(1)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reveals_teammates_as_soon_as_chat_updates_arrive() {
+        let lcu = fake_champ_select(2);
+        let emitter = Arc::new(RecordingEmitter::default());
+        let started_at = Instant::now();
+        let (bus, task) = spawn_tracker(&lcu, &emitter);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(emitter.payloads("champ_select_started").len(), 1);
+
+        let mut participants = chat_participants(EXPECTED_PARTICIPANT_COUNT);
+        participants.push(lobby::Participant {
+            cid: "party-chat".to_string(),
+            ..participant("friend-puuid", "Friend")
+        });
+        bus.publish(LcuEvent {
+            uri: lobby::CHAT_PARTICIPANTS_URI.to_string(),
+            event_type: "Update".to_string(),
+            data: json!({ "participants": participants }),
+        });
+        let team = task.await.unwrap().unwrap();
+
+        assert!(started_at.elapsed() < FALLBACK_POLL_INTERVAL);
+        let payloads = emitter.payloads("champ_select_started");
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(payloads[1]["participants"].as_array().unwrap().len(), 5);
+        assert_eq!(team.participants.len(), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn polls_when_no_updates_arrive() {
+        let lcu = fake_champ_select(2);
+        let emitter = Arc::new(RecordingEmitter::default());
+        let started_at = Instant::now();
+        let (_bus, task) = spawn_tracker(&lcu, &emitter);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        lcu.state().participants = chat_participants(EXPECTED_PARTICIPANT_COUNT);
+        let team = task.await.unwrap().unwrap();
+
+        assert!(started_at.elapsed() >= FALLBACK_POLL_INTERVAL);
+        assert_eq!(team.participants.len(), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stops_tracking_when_champ_select_ends() {
+        let lcu = fake_champ_select(2);
+        let emitter = Arc::new(RecordingEmitter::default());
+        let (bus, task) = spawn_tracker(&lcu, &emitter);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        bus.publish_phase(GameflowPhase::Lobby);
+
+        assert!(task.await.unwrap().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stops_tracking_when_the_client_disconnects() {
+        let lcu = fake_champ_select(2);
+        let emitter = Arc::new(RecordingEmitter::default());
+        let (bus, task) = spawn_tracker(&lcu, &emitter);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        bus.close();
+
+        assert!(task.await.unwrap().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stops_tracking_when_polling_sees_champ_select_end() {
+        let lcu = fake_champ_select(2);
+        let emitter = Arc::new(RecordingEmitter::default());
+        let (_bus, task) = spawn_tracker(&lcu, &emitter);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        lcu.state().gameflow_phase = GameflowPhase::None;
+
+        assert!(task.await.unwrap().is_none());
+    }
+
+    #[test]
+    fn parses_the_full_draft_state() {
+        let session: ChampSelectSession =
+            serde_json::from_str(include_str!("../fixtures/champ_select_session.json")).unwrap();
+
+        assert_eq!(session.local_player_cell_id, 2);
+        assert!(!session.is_spectating);
+        assert_eq!(session.their_team.len(), 5);
+        assert_eq!(session.their_team[0].puuid, "");
+        assert_eq!(session.my_team[2].champion_pick_intent, 103);
+        assert_eq!(session.my_team[2].spell2_id, 4);
+        assert_eq!(session.bans.my_team_bans, [266, 157]);
+        assert_eq!(session.bench_champions[0].champion_id, 64);
+
+        let turns: Vec<Vec<_>> = session
+            .actions
+            .iter()
+            .map(|turn| turn.iter().map(|action| action.kind).collect())
+            .collect();
+        assert_eq!(turns[0][0], ChampSelectActionType::Ban);
+        assert_eq!(turns[1][0], ChampSelectActionType::TenBansReveal);
+        assert_eq!(turns[2][0], ChampSelectActionType::Pick);
+
+        let in_progress: Vec<_> = session
+            .actions
+            .iter()
+            .flatten()
+            .filter(|action| action.is_in_progress)
+            .map(|action| action.actor_cell_id)
+            .collect();
+        assert_eq!(in_progress, [2]);
+    }
+
+    #[test]
+    fn defaults_missing_draft_fields() {
+        let session: ChampSelectSession = serde_json::from_value(json!({
+            "gameId": 7,
+            "timer": { "adjustedTimeLeftInPhase": 1_000, "phase": "PLANNING" },
+            "actions": [[{ "id": 1, "actorCellId": 0, "type": "vote" }]]
+        }))
+        .unwrap();
+
+        assert_eq!(session.local_player_cell_id, -1);
+        assert!(session.my_team.is_empty());
+        assert_eq!(session.actions[0][0].kind, ChampSelectActionType::Other);
+    }
 }
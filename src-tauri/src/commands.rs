@@ -1,12 +1,22 @@
 use crate::{
     app_state::{Dodge, Lcu},
-    champ_select::ChampSelectSession,
-    config::{self, AppConfig, Config},
-    lobby::get_lobby_info,
-    region::RegionInfo,
-    utils::display_champ_select,
+    config::{self, ActiveAccount, AppConfig, Config, ConfigView},
+    config_validation::{self, ConfigError},
+    lcu_api::{LcuApi, ShacoLcuApi},
+    live_client::{InGame, InGameRoster},
+    lobby::{get_lobby_info, Lobby, Participant},
+    lobby_history::{HistoryEntry, LegacyEntry, LobbyHistory},
+    logging::{self, Level},
+    multi_search::{self, ProviderInfo, Providers},
+    player_notes::{PlayerNote, PlayerNotes},
+    presets::{self, AppPresets, Preset},
+    profiles::{self, ActiveProfile},
+    region::Region,
+    supervisor::{PhaseSupervisor, TaskInfo},
+    teammate_stats,
+    utils::{create_multi_link, display_champ_select},
 };
-use shaco::rest::{LCUClientInfo, RESTClient};
+use shaco::rest::LCUClientInfo;
 use tauri::{AppHandle, Manager};
 
 type CommandResult<T> = Result<T, String>;
@@ -38,6 +48,14 @@ pub async fn app_ready(
     Ok(cfg.clone())
 }
 
+#[tauri::command]
+pub async fn get_active_profile(
+    cfg: tauri::State<'_, AppConfig>,
+    account: tauri::State<'_, ActiveAccount>,
+) -> CommandResult<Option<ActiveProfile>> {
+    Ok(profiles::active_profile(&cfg, &account).await)
+}
+
 #[tauri::command]
 pub async fn get_lcu_state(lcu: tauri::State<'_, Lcu>) -> CommandResult<bool> {
     let lcu = lcu.0.lock().await;
@@ -45,17 +63,21 @@ pub async fn get_lcu_state(lcu: tauri::State<'_, Lcu>) -> CommandResult<bool> {
 }
 
 #[tauri::command]
-pub async fn get_config(cfg: tauri::State<'_, AppConfig>) -> CommandResult<Config> {
-    let cfg = cfg.0.lock().await;
-    Ok(cfg.clone())
+pub async fn get_config(
+    cfg: tauri::State<'_, AppConfig>,
+    account: tauri::State<'_, ActiveAccount>,
+) -> CommandResult<ConfigView> {
+    Ok(config::view(&cfg, &account).await)
 }
 
+/// Saves `new_cfg` after clamping out-of-range numbers, returning what was
+/// stored. Values that cannot be clamped fail with one error per field.
 #[tauri::command]
 pub async fn set_config(
     cfg: tauri::State<'_, AppConfig>,
-    new_cfg: Config,
+    mut new_cfg: Config,
     app_handle: AppHandle,
-) -> CommandResult<()> {
+) -> Result<Config, ConfigError> {
     log_info!(
         "Saving settings: auto_open={}, auto_open_delay_seconds={}, auto_accept={}, accept_delay_ms={}, provider={}",
         new_cfg.auto_open,
@@ -64,49 +86,96 @@ pub async fn set_config(
         new_cfg.accept_delay,
         new_cfg.multi_provider
     );
+    let adjusted = config_validation::check(&mut new_cfg).map_err(|fields| {
+        log_warn!("Rejected settings: {} invalid fields", fields.len());
+        ConfigError::invalid(fields)
+    })?;
+    for error in adjusted {
+        log_info!("Adjusted setting {error}");
+    }
+
     let mut stored_config = cfg.0.lock().await;
     config::save(&app_handle, &new_cfg)
         .await
-        .map_err(|error| error.to_string())?;
-    *stored_config = new_cfg;
+        .map_err(ConfigError::other)?;
+    *stored_config = new_cfg.clone();
 
-    Ok(())
+    Ok(new_cfg)
 }
 
 #[tauri::command]
 pub async fn open_opgg_link(app_handle: AppHandle) -> CommandResult<()> {
     log_info!("Manual multi-search open requested");
-    let lcu_info = current_lcu_info(&app_handle).await?;
-    let app_client = RESTClient::new(lcu_info, false).map_err(|error| error.to_string())?;
+    let lcu = current_lcu_api(&app_handle).await?;
 
-    let config = {
-        let config = app_handle.state::<AppConfig>();
-        let value = config.0.lock().await.clone();
-        value
-    };
+    let config = config::effective(&app_handle).await;
 
-    let team = get_lobby_info(&app_client)
+    let team = get_lobby_info(&lcu)
         .await
         .map_err(|error| error.to_string())?;
-    let region_info: RegionInfo = serde_json::from_value(
-        app_client
-            .get("/riotclient/region-locale".to_string())
-            .await
-            .map_err(|error| error.to_string())?,
-    )
-    .map_err(|error| error.to_string())?;
-
-    let region = match region_info.web_region.as_str() {
-        "SG2" => "SG",
-        _ => &region_info.web_region,
+    let region = multi_search_region(&lcu).await?;
+
+    display_champ_select(&team, &region, &config).map_err(|error| error.to_string())?;
+
+    Ok(())
+}
+
+/// Opens the multi-search for the enemy team of the game in progress.
+#[tauri::command]
+pub async fn open_enemy_team_link(app_handle: AppHandle) -> CommandResult<()> {
+    log_info!("Enemy team multi-search open requested");
+    let lcu = current_lcu_api(&app_handle).await?;
+    let config = config::effective(&app_handle).await;
+
+    let enemies = {
+        let in_game = app_handle.state::<InGame>();
+        let roster = in_game.0.lock().await;
+        roster
+            .as_ref()
+            .map(InGameRoster::enemy_lobby)
+            .ok_or_else(|| "The enemy team is not known until the game has loaded".to_string())?
     };
+    let region = multi_search_region(&lcu).await?;
 
-    display_champ_select(&team, region, &config.multi_provider)
-        .map_err(|error| error.to_string())?;
+    display_champ_select(&enemies, &region, &config).map_err(|error| error.to_string())?;
 
     Ok(())
 }
 
+/// The built-in multi-search providers followed by the user's own.
+#[tauri::command]
+pub async fn get_multi_search_providers(
+    cfg: tauri::State<'_, AppConfig>,
+) -> CommandResult<Vec<ProviderInfo>> {
+    let cfg = cfg.0.lock().await;
+    let providers = Providers::from_config(&cfg).map_err(|error| format!("{error:#}"))?;
+    Ok(providers.list())
+}
+
+/// A multi-search link for `participants` using the selected provider, e.g.
+/// for a lobby from the history.
+#[tauri::command]
+pub async fn create_multi_search_link(
+    app_handle: AppHandle,
+    participants: Vec<Participant>,
+    region: String,
+) -> CommandResult<String> {
+    let cfg = config::effective(&app_handle).await;
+    let provider = multi_search::selected(&cfg).map_err(|error| format!("{error:#}"))?;
+    let region = Region::parse_or_other(&region).map_err(|error| error.to_string())?;
+    create_multi_link(&Lobby { participants }, &region, provider.as_ref())
+        .map_err(|error| error.to_string())
+}
+
+async fn multi_search_region(lcu: &impl LcuApi) -> CommandResult<Region> {
+    let region_info = lcu
+        .region_locale()
+        .await
+        .map_err(|error| error.to_string())?;
+
+    Region::try_from(&region_info).map_err(|error| error.to_string())
+}
+
 #[tauri::command]
 pub async fn get_lcu_info(lcu: tauri::State<'_, Lcu>) -> CommandResult<LCUClientInfo> {
     let lcu = lcu.0.lock().await;
@@ -117,15 +186,10 @@ pub async fn get_lcu_info(lcu: tauri::State<'_, Lcu>) -> CommandResult<LCUClient
 
 #[tauri::command]
 pub async fn dodge(app_handle: AppHandle) -> CommandResult<()> {
-    let lcu_info = current_lcu_info(&app_handle).await?;
-    let remoting_client = RESTClient::new(lcu_info, true).map_err(|error| error.to_string())?;
+    let lcu = current_lcu_api(&app_handle).await?;
 
     log_warn!("Manual Champ Select dodge requested");
-    remoting_client
-        .post(
-            "/lol-login/v1/session/invoke?destination=lcdsServiceProxy&method=call&args=[\"\",\"teambuilder-draft\",\"quitV2\",\"\"]".to_string(),
-            serde_json::json!({}),
-        )
+    lcu.quit_champ_select()
         .await
         .map_err(|error| error.to_string())?;
 
@@ -134,8 +198,7 @@ pub async fn dodge(app_handle: AppHandle) -> CommandResult<()> {
 
 #[tauri::command]
 pub async fn enable_dodge(app_handle: AppHandle) -> CommandResult<()> {
-    let lcu_info = current_lcu_info(&app_handle).await?;
-    let remoting_client = RESTClient::new(lcu_info, true).map_err(|error| error.to_string())?;
+    let lcu = current_lcu_api(&app_handle).await?;
 
     let dodge_state = app_handle.state::<Dodge>();
     let mut dodge_state = dodge_state.0.lock().await;
@@ -145,32 +208,179 @@ pub async fn enable_dodge(app_handle: AppHandle) -> CommandResult<()> {
         return Ok(());
     }
 
-    let champ_select = serde_json::from_value::<ChampSelectSession>(
-        remoting_client
-            .get("/lol-champ-select/v1/session".to_string())
-            .await
-            .map_err(|error| error.to_string())?,
-    )
-    .map_err(|error| error.to_string())?;
+    let champ_select = lcu
+        .champ_select_session()
+        .await
+        .map_err(|error| error.to_string())?;
 
     dodge_state.enabled = Some(champ_select.game_id);
     Ok(())
 }
 
-async fn current_lcu_info(app_handle: &AppHandle) -> CommandResult<LCUClientInfo> {
+async fn current_lcu_api(app_handle: &AppHandle) -> CommandResult<ShacoLcuApi> {
     let lcu = app_handle.state::<Lcu>();
-    let lcu = lcu.0.lock().await;
-    lcu.data
+    let lcu_info = lcu
+        .0
+        .lock()
+        .await
+        .data
         .clone()
-        .ok_or_else(|| "League Client is not connected".to_string())
+        .ok_or_else(|| "League Client is not connected".to_string())?;
+    ShacoLcuApi::new(lcu_info).map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+pub async fn get_presets(presets: tauri::State<'_, AppPresets>) -> CommandResult<Vec<Preset>> {
+    Ok(presets.0.lock().await.clone())
+}
+
+/// Merges presets from a JSON export into the saved ones and returns how many
+/// were imported.
+#[tauri::command]
+pub async fn import_presets(
+    presets: tauri::State<'_, AppPresets>,
+    json: String,
+    app_handle: AppHandle,
+) -> CommandResult<usize> {
+    let imported = presets::parse(&json).map_err(|error| format!("{error:#}"))?;
+    let count = imported.len();
+
+    let mut stored_presets = presets.0.lock().await;
+    let mut updated = stored_presets.clone();
+    presets::merge(&mut updated, imported);
+    presets::save(&app_handle, &updated)
+        .await
+        .map_err(|error| error.to_string())?;
+    *stored_presets = updated;
+
+    log_info!("Imported {count} champion presets");
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn export_presets(presets: tauri::State<'_, AppPresets>) -> CommandResult<String> {
+    let presets = presets.0.lock().await;
+    presets::export(&presets).map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+pub async fn get_lobby_history(
+    history: tauri::State<'_, LobbyHistory>,
+) -> CommandResult<Vec<HistoryEntry>> {
+    Ok(history.list().await)
+}
+
+#[tauri::command]
+pub async fn search_lobby_history(
+    history: tauri::State<'_, LobbyHistory>,
+    riot_id: String,
+) -> CommandResult<Vec<HistoryEntry>> {
+    Ok(history.search(&riot_id).await)
+}
+
+#[tauri::command]
+pub async fn delete_lobby_history_entry(
+    history: tauri::State<'_, LobbyHistory>,
+    game_id: u64,
+) -> CommandResult<bool> {
+    log_info!("Deleting lobby history entry for game {game_id}");
+    history
+        .delete(game_id)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_lobby_history(history: tauri::State<'_, LobbyHistory>) -> CommandResult<()> {
+    log_info!("Clearing lobby history");
+    history.clear().await.map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+pub async fn export_lobby_history(
+    history: tauri::State<'_, LobbyHistory>,
+) -> CommandResult<String> {
+    history.export().await.map_err(|error| error.to_string())
+}
+
+/// Moves the history the frontend kept in localStorage into the backend's
+/// file, returning how many entries were added.
+#[tauri::command]
+pub async fn import_lobby_history(
+    history: tauri::State<'_, LobbyHistory>,
+    entries: Vec<LegacyEntry>,
+) -> CommandResult<usize> {
+    history
+        .import(entries)
+        .await
+        .map_err(|error| format!("{error:#}"))
+}
+
+#[tauri::command]
+pub async fn get_player_notes(
+    notes: tauri::State<'_, PlayerNotes>,
+) -> CommandResult<Vec<PlayerNote>> {
+    Ok(notes.list().await)
+}
+
+/// Creates or replaces the note for a player.
+#[tauri::command]
+pub async fn save_player_note(
+    notes: tauri::State<'_, PlayerNotes>,
+    puuid: String,
+    riot_id: String,
+    note: String,
+    tags: Vec<String>,
+) -> CommandResult<PlayerNote> {
+    log_info!("Saving player note with {} tags", tags.len());
+    notes
+        .save(&puuid, &riot_id, &note, &tags)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_player_note(
+    notes: tauri::State<'_, PlayerNotes>,
+    puuid: String,
+) -> CommandResult<bool> {
+    log_info!("Deleting player note");
+    notes
+        .delete(&puuid)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+/// Looks up rank and recent games for each player, emitting `teammate_stats`
+/// as each lookup finishes. Resolves once every lookup is done.
+#[tauri::command]
+pub async fn get_teammate_stats(app_handle: AppHandle, puuids: Vec<String>) -> CommandResult<()> {
+    let lcu = current_lcu_api(&app_handle).await?;
+    teammate_stats::emit_teammate_stats(&lcu, &app_handle, &puuids).await;
+    Ok(())
+}
+
+/// Lists the phase tasks currently running, for debugging.
+#[tauri::command]
+pub fn get_running_tasks(supervisor: tauri::State<'_, PhaseSupervisor>) -> Vec<TaskInfo> {
+    supervisor.running_tasks()
 }
 
 #[tauri::command]
 pub fn write_frontend_log(level: String, message: String) {
-    let message = crate::logging::sanitize_frontend_message(&message);
+    let message = logging::sanitize_frontend_message(&message);
     match level.as_str() {
         "error" => log_error!("Frontend: {message}"),
         "warn" => log_warn!("Frontend: {message}"),
+        "debug" => log_debug!("Frontend: {message}"),
         _ => log_info!("Frontend: {message}"),
     }
 }
+
+/// Changes how verbose the log file is until Reveal restarts.
+#[tauri::command]
+pub fn set_log_level(level: Level) {
+    let previous = logging::level();
+    logging::set_level(level);
+    log_info!("Log level changed from {previous} to {level}");
+}
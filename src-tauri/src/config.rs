@@ -1,42 +1,244 @@
-use anyhow::{Context, Result};
+use crate::{
+    config_validation,
+    emitter::Emitter,
+    store::{self, Dir},
+    summoner::Summoner,
+};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use tauri::AppHandle;
+use serde_json::{Map, Value};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tauri::{AppHandle, Manager};
 use tokio::sync::Mutex;
 
 pub const DEFAULT_AUTO_OPEN_DELAY_SECONDS: u32 = 6;
+const CONFIG_FILE: &str = "config.json";
+/// How often `config.json` is checked for changes made outside Reveal.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The layout version written by this build. Files without a version predate
+/// versioning and count as version 0.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Upgrades a config object by one version; the migration at index `n` turns
+/// version `n` into version `n + 1`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); CONFIG_VERSION as usize] = [migrate_unversioned];
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
+    /// Missing when the frontend sends a config, which is always current.
+    #[serde(default = "current_version")]
+    pub version: u32,
     pub auto_open: bool,
     #[serde(default = "default_auto_open_delay_seconds")]
     pub auto_open_delay_seconds: u32,
     pub auto_accept: bool,
+    /// Milliseconds between a ready check popping and accepting it. Reveal
+    /// sees it about a second in, so the minimum of 1000 accepts it as soon
+    /// as it appears.
     pub accept_delay: u32,
-    #[serde(default = "default_provider")]
+    #[serde(default = "default_multi_provider")]
     pub multi_provider: String,
+    /// Multi-search sites added by the user, selectable like the built-in ones.
+    #[serde(default)]
+    pub custom_providers: Vec<CustomProvider>,
+    #[serde(default)]
+    pub ready_check_rules: ReadyCheckRules,
+    #[serde(default)]
+    pub auto_draft: AutoDraft,
+    /// Settings for specific accounts, applied over the ones above while one
+    /// of their accounts is logged in.
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+}
+
+/// Named settings for some of the user's accounts. Unset fields keep the
+/// top-level value.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Profile {
+    pub name: String,
+    /// Riot IDs (`name#tag`, in any case) or puuids.
+    pub accounts: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_open: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_open_delay_seconds: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_accept: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accept_delay: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multi_provider: Option<String>,
+}
+
+impl Profile {
+    pub fn matches(&self, summoner: &Summoner) -> bool {
+        let riot_id = summoner.riot_id();
+        self.accounts
+            .iter()
+            .map(|account| account.trim())
+            .filter(|account| !account.is_empty())
+            .any(|account| account == summoner.puuid || account.eq_ignore_ascii_case(&riot_id))
+    }
+}
+
+impl Config {
+    /// The first profile listing `summoner`.
+    pub fn profile_for(&self, summoner: &Summoner) -> Option<&Profile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.matches(summoner))
+    }
+
+    /// The settings in effect while `summoner` is logged in.
+    pub fn for_account(&self, summoner: Option<&Summoner>) -> Config {
+        let mut config = self.clone();
+        let Some(profile) = summoner.and_then(|summoner| self.profile_for(summoner)) else {
+            return config;
+        };
+
+        if let Some(auto_open) = profile.auto_open {
+            config.auto_open = auto_open;
+        }
+        if let Some(delay) = profile.auto_open_delay_seconds {
+            config.auto_open_delay_seconds = delay;
+        }
+        if let Some(auto_accept) = profile.auto_accept {
+            config.auto_accept = auto_accept;
+        }
+        if let Some(delay) = profile.accept_delay {
+            config.accept_delay = delay;
+        }
+        if let Some(provider) = &profile.multi_provider {
+            config.multi_provider = provider.clone();
+        }
+        config
+    }
+}
+
+/// A multi-search site defined by a URL template, see
+/// [`crate::multi_search`] for the placeholders.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomProvider {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub url: String,
+    /// Placed between a player's name and tag.
+    #[serde(default = "default_tag_separator")]
+    pub tag_separator: String,
+    /// Placed between players.
+    #[serde(default = "default_list_separator")]
+    pub list_separator: String,
+}
+
+/// Conditions that refine what auto-accept does with a ready check.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReadyCheckRules {
+    /// Queues auto-accept is limited to; empty allows every queue.
+    pub accept_queue_ids: Vec<i64>,
+    /// Queues that are never accepted automatically.
+    pub blacklisted_queue_ids: Vec<i64>,
+    /// Declines every ready check while set.
+    pub do_not_disturb: bool,
+    /// Local hours during which ready checks are declined.
+    pub decline_hours: Option<HourRange>,
+}
+
+/// Champion priorities for automatic bans and picks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AutoDraft {
+    pub auto_ban: bool,
+    pub auto_pick: bool,
+    /// The champion is hovered until this many seconds are left in the
+    /// phase, then locked in.
+    pub lock_at_seconds_left: u32,
+    /// Champion IDs to ban, best first, keyed by assigned position.
+    pub bans: BTreeMap<String, Vec<u32>>,
+    /// Champion IDs to pick, best first, keyed by assigned position.
+    pub picks: BTreeMap<String, Vec<u32>>,
+}
+
+/// Priority list used when none matches the assigned position, e.g. in
+/// blind pick where nobody has one.
+pub const ANY_POSITION: &str = "any";
+
+impl Default for AutoDraft {
+    fn default() -> Self {
+        Self {
+            auto_ban: false,
+            auto_pick: false,
+            lock_at_seconds_left: 5,
+            bans: BTreeMap::new(),
+            picks: BTreeMap::new(),
+        }
+    }
+}
+
+/// Hours of the day from `start` up to, but excluding, `end`. Wraps past
+/// midnight when `start` is after `end`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HourRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl HourRange {
+    pub fn contains(&self, hour: u32) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&hour)
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             auto_open: true,
-            auto_open_delay_seconds: DEFAULT_AUTO_OPEN_DELAY_SECONDS,
+            auto_open_delay_seconds: default_auto_open_delay_seconds(),
             auto_accept: false,
             accept_delay: 2_000,
-            multi_provider: default_provider(),
+            multi_provider: default_multi_provider(),
+            custom_providers: Vec::new(),
+            ready_check_rules: ReadyCheckRules::default(),
+            auto_draft: AutoDraft::default(),
+            profiles: Vec::new(),
         }
     }
 }
 
+fn current_version() -> u32 {
+    CONFIG_VERSION
+}
+
 fn default_auto_open_delay_seconds() -> u32 {
     DEFAULT_AUTO_OPEN_DELAY_SECONDS
 }
 
-fn default_provider() -> String {
+fn default_multi_provider() -> String {
     "opgg".to_string()
 }
 
+fn default_tag_separator() -> String {
+    "#".to_string()
+}
+
+fn default_list_separator() -> String {
+    ",".to_string()
+}
+
 pub struct AppConfig(pub Mutex<Config>);
 
 impl AppConfig {
@@ -45,63 +247,476 @@ impl AppConfig {
     }
 }
 
-pub fn load(app_handle: &AppHandle) -> Result<Config> {
-    let config_dir = app_handle
-        .path_resolver()
-        .app_config_dir()
-        .context("Reveal has no application config directory")?;
-    std::fs::create_dir_all(&config_dir).context("failed to create config directory")?;
+/// The account logged in to the League Client, once Reveal has seen one.
+#[derive(Default)]
+pub struct ActiveAccount(pub Mutex<Option<Summoner>>);
+
+/// What the frontend is sent about the config: the saved settings, which the
+/// settings page edits, and what applies with the active profile.
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigView {
+    pub config: Config,
+    pub effective: Config,
+}
+
+pub async fn view(config: &AppConfig, account: &ActiveAccount) -> ConfigView {
+    let account = account.0.lock().await.clone();
+    let config = config.0.lock().await.clone();
+    ConfigView {
+        effective: config.for_account(account.as_ref()),
+        config,
+    }
+}
+
+/// The config with the profile of the logged-in account applied. Features
+/// should read this rather than [`AppConfig`] directly.
+pub async fn effective(app_handle: &AppHandle) -> Config {
+    effective_for(
+        app_handle.state::<AppConfig>().inner(),
+        app_handle.state::<ActiveAccount>().inner(),
+    )
+    .await
+}
+
+/// [`effective`] for state held outside the app.
+pub async fn effective_for(config: &AppConfig, account: &ActiveAccount) -> Config {
+    let account = account.0.lock().await.clone();
+    let config = config.0.lock().await;
+    config.for_account(account.as_ref())
+}
+
+/// Fills in the fields added before configs were versioned.
+fn migrate_unversioned(config: &mut Map<String, Value>) {
+    config
+        .entry("autoOpenDelaySeconds")
+        .or_insert(DEFAULT_AUTO_OPEN_DELAY_SECONDS.into());
+    config
+        .entry("multiProvider")
+        .or_insert_with(|| default_multi_provider().into());
+}
+
+/// Parses a config file, migrating older layouts. Also returns the version
+/// the file was written in; one newer than [`CONFIG_VERSION`] is read as far
+/// as this build understands it.
+fn parse(json: &str) -> Result<(Config, u32)> {
+    let mut value: Value = serde_json::from_str(json).context("config is not valid JSON")?;
+    let Some(object) = value.as_object_mut() else {
+        bail!("config is not a JSON object");
+    };
+
+    let version = match object.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .context("config version is not a number")?,
+    };
+    for migration in MIGRATIONS.iter().skip(version as usize) {
+        migration(object);
+    }
+    object.insert("version".to_string(), CONFIG_VERSION.into());
 
-    let config_path = config_dir.join("config.json");
+    let config = serde_json::from_value(value).context("config does not match the schema")?;
+    Ok((config, version))
+}
+
+fn config_path(app_handle: &AppHandle) -> Result<PathBuf> {
+    store::path(app_handle, Dir::Config, CONFIG_FILE)
+}
+
+pub fn load(app_handle: &AppHandle) -> Result<Config> {
+    let config_path = config_path(app_handle)?;
     log_info!("Loading config from {}", config_path.display());
-    if !config_path.exists() {
+    Ok(load_from(&config_path))
+}
+
+/// Reads the config at `path`, falling back to the defaults when it is
+/// missing or unreadable. A file that cannot be parsed is kept next to it as
+/// a backup and replaced. A file from a newer build is read but never
+/// rewritten here.
+fn load_from(path: &Path) -> Config {
+    if !path.exists() {
         let config = Config::default();
-        let json = serde_json::to_string(&config).context("failed to serialize default config")?;
-        std::fs::write(&config_path, json).context("failed to write default config")?;
-        log_info!("Created a default config");
-        return Ok(config);
+        match write_sync(path, &config) {
+            Ok(()) => log_info!("Created a default config"),
+            Err(error) => log_error!("Failed to write the default config: {error:#}"),
+        }
+        return config;
     }
 
-    let json = std::fs::read_to_string(&config_path).context("failed to read config")?;
-    let config = serde_json::from_str(&json).context("failed to parse config")?;
-    log_info!("Config loaded successfully");
-    Ok(config)
+    let json = match std::fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(error) => {
+            log_error!("Failed to read config, using defaults: {error}");
+            return Config::default();
+        }
+    };
+
+    match parse(&json) {
+        Ok((mut config, version)) => {
+            repair_and_save(path, &mut config, version);
+            log_info!("Config loaded successfully");
+            config
+        }
+        Err(error) => {
+            log_error!("Config is corrupt, restoring defaults: {error:#}");
+            match store::back_up(path, "corrupt") {
+                Ok(backup_path) => {
+                    log_info!("Backed up the corrupt config to {}", backup_path.display())
+                }
+                Err(error) => log_error!("Failed to back up the corrupt config: {error:#}"),
+            }
+
+            let config = Config::default();
+            if let Err(error) = write_sync(path, &config) {
+                log_error!("Failed to write the default config: {error:#}");
+            }
+            config
+        }
+    }
 }
 
-pub async fn save(app_handle: &AppHandle, config: &Config) -> Result<()> {
-    let config_dir = app_handle
-        .path_resolver()
-        .app_config_dir()
-        .context("Reveal has no application config directory")?;
-    let config_path = config_dir.join("config.json");
+/// Repairs invalid values in a config just read from `path` and saves it
+/// back when it was migrated or repaired, unless a newer build wrote it.
+fn repair_and_save(path: &Path, config: &mut Config, version: u32) {
+    let adjusted = config_validation::repair(config);
+    for error in &adjusted {
+        log_warn!("Invalid config value {error}");
+    }
+    let migrated = version < CONFIG_VERSION;
+    if version > CONFIG_VERSION {
+        log_warn!(
+            "Config version {version} is newer than this build; reading it without saving over it"
+        );
+    } else if migrated || !adjusted.is_empty() {
+        match write_sync(path, config) {
+            Ok(()) if migrated => {
+                log_info!("Migrated config to version {CONFIG_VERSION}")
+            }
+            Ok(()) => log_info!("Saved the repaired config"),
+            Err(error) => log_error!("Failed to save the updated config: {error:#}"),
+        }
+    }
+}
+
+fn write_sync(path: &Path, config: &Config) -> Result<()> {
     let json = serde_json::to_string(config).context("failed to serialize config")?;
+    store::write_sync(path, json)
+}
 
-    tokio::fs::write(config_path, json)
-        .await
-        .context("failed to write config")?;
+pub async fn save(app_handle: &AppHandle, config: &Config) -> Result<()> {
+    let path = config_path(app_handle)?;
+    back_up_if_newer(&path).await?;
+    write(&path, config).await?;
     log_info!("Config saved successfully");
     Ok(())
 }
 
+/// Moves a config written by a newer build aside before this build replaces
+/// it, so going back to the newer build does not lose its settings.
+async fn back_up_if_newer(path: &Path) -> Result<()> {
+    let Ok(json) = tokio::fs::read_to_string(path).await else {
+        return Ok(());
+    };
+    let Ok((_, version)) = parse(&json) else {
+        return Ok(());
+    };
+    if version > CONFIG_VERSION {
+        let backup_path = store::back_up(path, &format!("v{version}"))?;
+        log_warn!(
+            "Kept the config from version {version} at {}",
+            backup_path.display()
+        );
+    }
+    Ok(())
+}
+
+async fn write(path: &Path, config: &Config) -> Result<()> {
+    let json = serde_json::to_string(config).context("failed to serialize config")?;
+    store::write(path, json).await
+}
+
+/// Reloads the config whenever `config.json` changes on disk, e.g. when it is
+/// edited by hand or synced from another machine. Edits that do not parse are
+/// logged and ignored so a half-saved file never replaces working settings.
+pub async fn watch(app_handle: AppHandle) {
+    let path = match config_path(&app_handle) {
+        Ok(path) => path,
+        Err(error) => {
+            log_error!("Cannot watch the config file: {error:#}");
+            return;
+        }
+    };
+
+    log_info!("Watching {} for changes", path.display());
+    let mut last_modified = modified_at(&path);
+    let mut interval = tokio::time::interval(WATCH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let modified = modified_at(&path);
+        if modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        let stored = app_handle.state::<AppConfig>();
+        let account = app_handle.state::<ActiveAccount>();
+        if let Err(error) = reload(&path, &stored, &account, &app_handle).await {
+            log_warn!("Ignoring the edited config: {error:#}");
+        }
+    }
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Reads `path` into `stored` and emits `config_updated` if anything changed.
+/// Saving from the app also touches the file, but then nothing differs.
+async fn reload(
+    path: &Path,
+    stored: &AppConfig,
+    account: &ActiveAccount,
+    emitter: &impl Emitter,
+) -> Result<bool> {
+    let json = tokio::fs::read_to_string(path)
+        .await
+        .context("failed to read config")?;
+    let (mut config, version) = parse(&json)?;
+    repair_and_save(path, &mut config, version);
+
+    let mut stored = stored.0.lock().await;
+    if serde_json::to_value(&config)? == serde_json::to_value(&*stored)? {
+        return Ok(false);
+    }
+    *stored = config.clone();
+    drop(stored);
+
+    log_info!("Reloaded the config after it changed on disk");
+    let effective = config.for_account(account.0.lock().await.as_ref());
+    emitter.emit_event("config_updated", ConfigView { config, effective })?;
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::emitter::RecordingEmitter;
 
     #[test]
     fn old_configs_receive_the_default_auto_open_delay() {
-        let config: Config = serde_json::from_str(
+        let (config, version) = parse(
             r#"{
                 "autoOpen": true,
                 "autoAccept": false,
-                "acceptDelay": 2000,
-                "multiProvider": "opgg"
+                "acceptDelay": 2000
             }"#,
         )
         .unwrap();
 
+        assert_eq!(version, 0);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(
+            config.auto_open_delay_seconds,
+            DEFAULT_AUTO_OPEN_DELAY_SECONDS
+        );
+        assert_eq!(config.multi_provider, "opgg");
+        assert_eq!(config.ready_check_rules, ReadyCheckRules::default());
+
+        let current = serde_json::to_string(&config).unwrap();
+        assert_eq!(parse(&current).unwrap().1, CONFIG_VERSION);
+    }
+
+    #[test]
+    fn replaces_a_corrupt_config_and_keeps_a_backup() {
+        let dir = store::temporary_dir("config-corrupt");
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(&path, r#"{"autoOpen": tru"#).unwrap();
+
+        let config = load_from(&path);
+
+        assert!(config.auto_open);
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(parse(&saved).unwrap().0.version, CONFIG_VERSION);
+        let files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        let backup = files
+            .iter()
+            .find(|name| name.starts_with("config.corrupt-"))
+            .expect("a backup of the corrupt file");
+        assert_eq!(
+            std::fs::read_to_string(dir.join(backup)).unwrap(),
+            r#"{"autoOpen": tru"#
+        );
+        assert_eq!(files.len(), 2);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn keeps_configs_from_newer_builds() {
+        let dir = store::temporary_dir("config-newer");
+        let path = dir.join(CONFIG_FILE);
+        let newer = r#"{"version": 99, "autoOpen": false, "autoAccept": true,
+            "acceptDelay": 0, "futureSetting": 1}"#;
+        std::fs::write(&path, newer).unwrap();
+
+        let config = load_from(&path);
+
+        assert!(!config.auto_open);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), newer);
+
+        back_up_if_newer(&path).await.unwrap();
+        write(&path, &config).await.unwrap();
+        let backup = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.to_string_lossy().contains("config.v99-"))
+            .expect("a backup of the newer file");
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), newer);
+        assert_eq!(
+            parse(&std::fs::read_to_string(&path).unwrap()).unwrap().1,
+            CONFIG_VERSION
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn fills_in_fields_missing_from_current_configs() {
+        let dir = store::temporary_dir("config-missing-fields");
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(
+            &path,
+            r#"{"version": 1, "autoOpen": false, "autoAccept": true, "acceptDelay": 3000}"#,
+        )
+        .unwrap();
+
+        let config = load_from(&path);
+
+        assert!(!config.auto_open);
         assert_eq!(
             config.auto_open_delay_seconds,
             DEFAULT_AUTO_OPEN_DELAY_SECONDS
         );
+        assert_eq!(config.multi_provider, "opgg");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn saves_through_a_temporary_file() {
+        let dir = store::temporary_dir("config-save");
+        let path = dir.join(CONFIG_FILE);
+        let config = Config {
+            accept_delay: 3_000,
+            ..Config::default()
+        };
+
+        write(&path, &config).await.unwrap();
+
+        assert_eq!(load_from(&path).accept_delay, 3_000);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reloads_edited_files_and_ignores_broken_ones() {
+        let dir = store::temporary_dir("config-reload");
+        let path = dir.join(CONFIG_FILE);
+        let stored = AppConfig::new(Config::default());
+        let account = ActiveAccount::default();
+        *account.0.lock().await = Some(Summoner {
+            game_name: "Smurf".to_string(),
+            tag_line: "EUW".to_string(),
+            puuid: "smurf-puuid".to_string(),
+        });
+        let emitter = RecordingEmitter::default();
+
+        write(&path, &Config::default()).await.unwrap();
+        assert!(!reload(&path, &stored, &account, &emitter).await.unwrap());
+
+        std::fs::write(
+            &path,
+            r#"{"version": 1, "autoOpen": false, "autoOpenDelaySeconds": 6,
+                "autoAccept": true, "acceptDelay": 0, "multiProvider": "ugg",
+                "profiles": [{"name": "Smurf", "accounts": ["smurf#euw"],
+                              "multiProvider": "opgg"}]}"#,
+        )
+        .unwrap();
+        assert!(reload(&path, &stored, &account, &emitter).await.unwrap());
+        {
+            let config = stored.0.lock().await;
+            assert!(config.auto_accept);
+            assert_eq!(config.accept_delay, 1_000);
+            assert_eq!(config.multi_provider, "ugg");
+        }
+        let payloads = emitter.payloads("config_updated");
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0]["config"]["acceptDelay"], 1_000);
+        assert_eq!(payloads[0]["config"]["multiProvider"], "ugg");
+        assert_eq!(payloads[0]["effective"]["multiProvider"], "opgg");
+        let (saved, _) = parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.accept_delay, 1_000);
+
+        std::fs::write(&path, r#"{"autoOpen": tru"#).unwrap();
+        assert!(reload(&path, &stored, &account, &emitter).await.is_err());
+        assert!(stored.0.lock().await.auto_accept);
+        assert_eq!(emitter.payloads("config_updated").len(), 1);
+
+        let newer = format!(
+            r#"{{"version": {}, "autoOpen": true, "autoAccept": false, "acceptDelay": 0,
+                "futureSetting": true}}"#,
+            CONFIG_VERSION + 1
+        );
+        std::fs::write(&path, &newer).unwrap();
+        assert!(reload(&path, &stored, &account, &emitter).await.unwrap());
+        assert!(!stored.0.lock().await.auto_accept);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), newer);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn profiles_override_settings_for_their_accounts() {
+        let config = Config {
+            profiles: vec![Profile {
+                name: "Smurf".to_string(),
+                accounts: vec!["smurf#euw".to_string(), "main-puuid".to_string()],
+                auto_accept: Some(true),
+                multi_provider: Some("ugg".to_string()),
+                ..Profile::default()
+            }],
+            ..Config::default()
+        };
+        let account = |game_name: &str, puuid: &str| Summoner {
+            game_name: game_name.to_string(),
+            tag_line: "EUW".to_string(),
+            puuid: puuid.to_string(),
+        };
+
+        let smurf = config.for_account(Some(&account("Smurf", "smurf-puuid")));
+        assert!(smurf.auto_accept);
+        assert_eq!(smurf.multi_provider, "ugg");
+        assert_eq!(smurf.accept_delay, config.accept_delay);
+        assert!(config.profile_for(&account("Main", "main-puuid")).is_some());
+
+        let other = config.for_account(Some(&account("Other", "other-puuid")));
+        assert!(!other.auto_accept);
+        assert!(!config.for_account(None).auto_accept);
+    }
+
+    #[test]
+    fn hour_ranges_wrap_past_midnight() {
+        let evening = HourRange { start: 18, end: 22 };
+        assert!(evening.contains(18));
+        assert!(!evening.contains(22));
+
+        let night = HourRange { start: 23, end: 7 };
+        assert!(night.contains(23));
+        assert!(night.contains(3));
+        assert!(!night.contains(7));
+        assert!(!night.contains(12));
     }
 }
@@ -0,0 +1,328 @@
+use crate::{
+    config::{Config, HourRange, Profile, ANY_POSITION},
+    multi_search::Providers,
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    ops::RangeInclusive,
+};
+
+const AUTO_OPEN_DELAY_SECONDS: RangeInclusive<u32> = 0..=60;
+/// Ready checks last about ten seconds, and the first second is spent before
+/// Reveal sees them, so a delay below that cannot be honoured.
+const ACCEPT_DELAY_MS: RangeInclusive<u32> = 1_000..=10_000;
+const LOCK_AT_SECONDS_LEFT: RangeInclusive<u32> = 0..=30;
+const POSITIONS: [&str; 6] = ["top", "jungle", "middle", "bottom", "utility", ANY_POSITION];
+
+/// A problem with one config field. `field` is the camelCase path the
+/// frontend uses, e.g. `readyCheckRules.declineHours`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Why `set_config` did not save a config.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigError {
+    pub message: String,
+    /// Empty when saving failed for another reason.
+    pub fields: Vec<FieldError>,
+}
+
+impl ConfigError {
+    pub fn invalid(fields: Vec<FieldError>) -> Self {
+        let message = fields
+            .iter()
+            .map(FieldError::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+        Self { message, fields }
+    }
+
+    pub fn other(error: impl fmt::Display) -> Self {
+        Self {
+            message: error.to_string(),
+            fields: Vec::new(),
+        }
+    }
+}
+
+/// Clamps out-of-range numbers, then fails with every value that cannot be
+/// fixed that way. Returns the fields that were clamped.
+pub fn check(config: &mut Config) -> Result<Vec<FieldError>, Vec<FieldError>> {
+    let report = validate(config, false);
+    if report.rejected.is_empty() {
+        Ok(report.adjusted)
+    } else {
+        Err(report.rejected)
+    }
+}
+
+/// Like [`check`], but replaces values that cannot be clamped with their
+/// defaults. Used for files, which may have been edited by hand.
+pub fn repair(config: &mut Config) -> Vec<FieldError> {
+    validate(config, true).adjusted
+}
+
+struct Report {
+    repair: bool,
+    adjusted: Vec<FieldError>,
+    rejected: Vec<FieldError>,
+}
+
+impl Report {
+    fn clamp(&mut self, field: &str, value: &mut u32, range: RangeInclusive<u32>) {
+        let clamped = (*value).clamp(*range.start(), *range.end());
+        if clamped != *value {
+            self.adjusted.push(FieldError {
+                field: field.to_string(),
+                message: format!(
+                    "{value} is outside {}..={}, using {clamped}",
+                    range.start(),
+                    range.end()
+                ),
+            });
+            *value = clamped;
+        }
+    }
+
+    /// Records an invalid value, returning whether the caller should replace
+    /// it with its default.
+    fn reject(&mut self, field: impl Into<String>, message: impl Into<String>) -> bool {
+        let error = FieldError {
+            field: field.into(),
+            message: message.into(),
+        };
+        if self.repair {
+            self.adjusted.push(FieldError {
+                message: format!("{}, using the default", error.message),
+                ..error
+            });
+        } else {
+            self.rejected.push(error);
+        }
+        self.repair
+    }
+}
+
+fn validate(config: &mut Config, repair: bool) -> Report {
+    let mut report = Report {
+        repair,
+        adjusted: Vec::new(),
+        rejected: Vec::new(),
+    };
+    let defaults = Config::default();
+
+    report.clamp(
+        "autoOpenDelaySeconds",
+        &mut config.auto_open_delay_seconds,
+        AUTO_OPEN_DELAY_SECONDS,
+    );
+    report.clamp("acceptDelay", &mut config.accept_delay, ACCEPT_DELAY_MS);
+    report.clamp(
+        "autoDraft.lockAtSecondsLeft",
+        &mut config.auto_draft.lock_at_seconds_left,
+        LOCK_AT_SECONDS_LEFT,
+    );
+
+    let mut providers = Providers::builtin();
+    let mut custom_providers = Vec::new();
+    for (index, custom) in config.custom_providers.iter().enumerate() {
+        match providers.add_custom(custom) {
+            Ok(()) => custom_providers.push(custom.clone()),
+            Err(error) => {
+                report.reject(format!("customProviders.{index}"), format!("{error:#}"));
+            }
+        }
+    }
+    if repair {
+        config.custom_providers = custom_providers;
+    }
+    if providers.get(&config.multi_provider).is_none() {
+        let message = format!("unknown multi-search provider {:?}", config.multi_provider);
+        if report.reject("multiProvider", message) {
+            config.multi_provider = defaults.multi_provider;
+        }
+    }
+
+    if let Some(message) = config
+        .ready_check_rules
+        .decline_hours
+        .and_then(hour_range_error)
+    {
+        if report.reject("readyCheckRules.declineHours", message) {
+            config.ready_check_rules.decline_hours = None;
+        }
+    }
+
+    check_priorities(&mut report, "autoDraft.bans", &mut config.auto_draft.bans);
+    check_priorities(&mut report, "autoDraft.picks", &mut config.auto_draft.picks);
+    check_profiles(&mut report, &providers, &mut config.profiles);
+
+    report
+}
+
+fn check_profiles(report: &mut Report, providers: &Providers, profiles: &mut Vec<Profile>) {
+    let mut names = HashSet::new();
+    let mut index = 0;
+    profiles.retain_mut(|profile| {
+        let field = format!("profiles.{index}");
+        index += 1;
+
+        let name = profile.name.trim();
+        if name.is_empty() {
+            return !report.reject(field, "profiles need a name");
+        }
+        if !names.insert(name.to_string()) {
+            return !report.reject(field, format!("profile {name} is defined more than once"));
+        }
+
+        if let Some(delay) = profile.auto_open_delay_seconds.as_mut() {
+            report.clamp(
+                &format!("{field}.autoOpenDelaySeconds"),
+                delay,
+                AUTO_OPEN_DELAY_SECONDS,
+            );
+        }
+        if let Some(delay) = profile.accept_delay.as_mut() {
+            report.clamp(&format!("{field}.acceptDelay"), delay, ACCEPT_DELAY_MS);
+        }
+        if let Some(provider) = &profile.multi_provider {
+            if providers.get(provider).is_none()
+                && report.reject(
+                    format!("{field}.multiProvider"),
+                    format!("unknown multi-search provider {provider:?}"),
+                )
+            {
+                profile.multi_provider = None;
+            }
+        }
+        true
+    });
+}
+
+fn hour_range_error(hours: HourRange) -> Option<String> {
+    if hours.start > 23 || hours.end > 23 {
+        Some("hours must be between 0 and 23".to_string())
+    } else if hours.start == hours.end {
+        Some("the start and end hour must differ".to_string())
+    } else {
+        None
+    }
+}
+
+fn check_priorities(report: &mut Report, field: &str, priorities: &mut BTreeMap<String, Vec<u32>>) {
+    priorities.retain(|position, champion_ids| {
+        if !POSITIONS.contains(&position.as_str()) {
+            return !report.reject(
+                format!("{field}.{position}"),
+                format!("unknown position, expected one of {}", POSITIONS.join(", ")),
+            );
+        }
+        if champion_ids.contains(&0)
+            && report.reject(format!("{field}.{position}"), "0 is not a champion ID")
+        {
+            champion_ids.retain(|&champion_id| champion_id != 0);
+        }
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CustomProvider;
+
+    fn invalid_config() -> Config {
+        let mut config = Config {
+            accept_delay: 0,
+            auto_open_delay_seconds: 3_600,
+            multi_provider: "mine".to_string(),
+            custom_providers: vec![CustomProvider {
+                id: "mine".to_string(),
+                name: "Mine".to_string(),
+                url: "example.com/{riotIds}".to_string(),
+                tag_separator: "#".to_string(),
+                list_separator: ",".to_string(),
+            }],
+            ..Config::default()
+        };
+        config.ready_check_rules.decline_hours = Some(HourRange { start: 22, end: 25 });
+        config.auto_draft.picks.insert("mid".to_string(), vec![103]);
+        config
+            .auto_draft
+            .bans
+            .insert("jungle".to_string(), vec![0, 64]);
+        config.profiles = vec![
+            Profile {
+                name: "Smurf".to_string(),
+                accept_delay: Some(60_000),
+                multi_provider: Some("unknown".to_string()),
+                ..Profile::default()
+            },
+            Profile {
+                name: " Smurf ".to_string(),
+                ..Profile::default()
+            },
+        ];
+        config
+    }
+
+    fn fields(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|error| error.field.as_str()).collect()
+    }
+
+    #[test]
+    fn clamps_numbers_and_rejects_the_rest() {
+        let mut config = invalid_config();
+
+        let errors = check(&mut config).unwrap_err();
+
+        assert_eq!(
+            fields(&errors),
+            [
+                "customProviders.0",
+                "multiProvider",
+                "readyCheckRules.declineHours",
+                "autoDraft.bans.jungle",
+                "autoDraft.picks.mid",
+                "profiles.0.multiProvider",
+                "profiles.1",
+            ]
+        );
+        assert_eq!(config.accept_delay, 1_000);
+        assert_eq!(config.auto_open_delay_seconds, 60);
+        assert_eq!(config.profiles[0].accept_delay, Some(10_000));
+
+        let mut valid = Config::default();
+        assert_eq!(check(&mut valid), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn repairs_hand_edited_files_with_defaults() {
+        let mut config = invalid_config();
+
+        let adjusted = repair(&mut config);
+
+        assert_eq!(adjusted.len(), 10);
+        assert!(config.custom_providers.is_empty());
+        assert_eq!(config.multi_provider, "opgg");
+        assert_eq!(config.ready_check_rules.decline_hours, None);
+        assert_eq!(config.auto_draft.bans["jungle"], [64]);
+        assert!(!config.auto_draft.picks.contains_key("mid"));
+        assert_eq!(config.profiles.len(), 1);
+        assert_eq!(config.profiles[0].multi_provider, None);
+        assert!(check(&mut config).is_ok());
+    }
+}
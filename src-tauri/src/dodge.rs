@@ -0,0 +1,189 @@
+use crate::{
+    app_state::DodgeState,
+    champ_select::{self, ChampSelectSession, SessionEvent, SESSION_URI},
+    event_bus::EventBus,
+    gameflow::GameflowPhase,
+    lcu_api::LcuApi,
+    logging,
+    state::PhaseContext,
+};
+use std::time::Duration;
+
+/// How long before the timer runs out to dodge. The game starts when it does,
+/// and that cancels the Champ Select tasks, including the dodge.
+const DODGE_MARGIN: Duration = Duration::from_millis(500);
+
+/// Watches Champ Select for a game with the last-second dodge enabled.
+pub fn register(bus: &EventBus, context: &impl PhaseContext, lcu: &impl LcuApi) {
+    let mut sessions = bus.subscribe(SESSION_URI);
+    let context = context.clone();
+    let lcu = lcu.clone();
+
+    tauri::async_runtime::spawn(logging::inherit(async move {
+        while let Some(event) = champ_select::next_session(&mut sessions).await {
+            // The session is deleted when Champ Select ends; there is nothing to dodge.
+            if let SessionEvent::Update(champ_select) = event {
+                handle_last_second_dodge(&champ_select, &context, &lcu).await;
+            }
+        }
+    }));
+}
+
+async fn handle_last_second_dodge(
+    champ_select: &ChampSelectSession,
+    context: &impl PhaseContext,
+    lcu: &impl LcuApi,
+) {
+    log_trace!(
+        "Checking the last-second dodge for game {} in phase {}",
+        champ_select.game_id,
+        champ_select.timer.phase
+    );
+    let mut dodge = context.dodge().0.lock().await;
+    let Some(delay) = last_second_dodge_delay(champ_select, &mut dodge) else {
+        return;
+    };
+    drop(dodge);
+
+    let lcu = lcu.clone();
+    log_warn!("Last-second dodge scheduled after {delay:?}");
+
+    context.supervisor().start(
+        GameflowPhase::ChampSelect,
+        "last_second_dodge",
+        logging::scoped(format!("game-{}", champ_select.game_id), async move {
+            dodge_after(&lcu, delay).await
+        }),
+    );
+}
+
+/// Returns how long to wait before dodging, once per game that has the
+/// last-second dodge enabled and has reached finalization.
+fn last_second_dodge_delay(
+    champ_select: &ChampSelectSession,
+    dodge: &mut DodgeState,
+) -> Option<Duration> {
+    if champ_select.timer.phase != "FINALIZATION" {
+        return None;
+    }
+
+    let game_id = champ_select.game_id;
+    if dodge.last_dodge == Some(game_id) {
+        log_trace!("The last-second dodge for game {game_id} is already scheduled");
+        return None;
+    }
+    if dodge.enabled != Some(game_id) {
+        log_debug!("Not dodging game {game_id}; the last-second dodge is off");
+        return None;
+    }
+
+    dodge.last_dodge = Some(game_id);
+    Some(
+        Duration::from_millis(champ_select.timer.adjusted_time_left_in_phase)
+            .saturating_sub(DODGE_MARGIN),
+    )
+}
+
+async fn dodge_after(lcu: &impl LcuApi, delay: Duration) {
+    tokio::time::sleep(delay).await;
+    log_debug!("Dodge delay of {delay:?} elapsed");
+    log_warn!("Calling the last-second dodge endpoint");
+    if let Err(error) = lcu.quit_champ_select().await {
+        log_error!("Last-second dodge failed: {error}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config, lcu_api::fake::FakeLcuApi, state::fake::TestContext, websocket::LcuEvent,
+    };
+
+    fn session(phase: &str) -> ChampSelectSession {
+        serde_json::from_value(serde_json::json!({
+            "gameId": 42,
+            "timer": { "adjustedTimeLeftInPhase": 4_500, "phase": phase }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn schedules_the_last_second_dodge_once_in_finalization() {
+        let mut dodge = DodgeState {
+            last_dodge: None,
+            enabled: Some(42),
+        };
+
+        assert_eq!(
+            last_second_dodge_delay(&session("BAN_PICK"), &mut dodge),
+            None
+        );
+        assert_eq!(
+            last_second_dodge_delay(&session("FINALIZATION"), &mut dodge),
+            Some(Duration::from_millis(4_000))
+        );
+        assert_eq!(
+            last_second_dodge_delay(&session("FINALIZATION"), &mut dodge),
+            None
+        );
+    }
+
+    #[test]
+    fn ignores_games_without_the_dodge_enabled() {
+        let mut dodge = DodgeState {
+            last_dodge: None,
+            enabled: Some(7),
+        };
+        assert_eq!(
+            last_second_dodge_delay(&session("FINALIZATION"), &mut dodge),
+            None
+        );
+
+        dodge.enabled = None;
+        assert_eq!(
+            last_second_dodge_delay(&session("FINALIZATION"), &mut dodge),
+            None
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn quits_champ_select_when_the_dodge_delay_elapses() {
+        let lcu = FakeLcuApi::default();
+        let task = tokio::spawn({
+            let lcu = lcu.clone();
+            async move { dodge_after(&lcu, Duration::from_millis(4_500)).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(4_000)).await;
+        assert!(lcu.actions().is_empty());
+
+        task.await.unwrap();
+        assert_eq!(lcu.actions(), ["quit_champ_select"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn quits_before_the_game_start_cancels_champ_select() {
+        let lcu = FakeLcuApi::default();
+        let context = TestContext::new(Config::default());
+        context.dodge().0.lock().await.enabled = Some(42);
+        let bus = EventBus::default();
+        register(&bus, &context, &lcu);
+
+        bus.publish(LcuEvent {
+            uri: SESSION_URI.to_string(),
+            event_type: "Update".to_string(),
+            data: serde_json::json!({
+                "gameId": 42,
+                "timer": { "adjustedTimeLeftInPhase": 4_500, "phase": "FINALIZATION" }
+            }),
+        });
+        // The game starts as the timer runs out, ending the phase.
+        tokio::time::sleep(Duration::from_millis(4_500)).await;
+        context
+            .supervisor()
+            .cancel_phase(&GameflowPhase::ChampSelect);
+
+        assert_eq!(lcu.actions(), ["quit_champ_select"]);
+    }
+}
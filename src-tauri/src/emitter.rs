@@ -0,0 +1,41 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+/// Destination for events sent to the frontend.
+pub trait Emitter: Send + Sync {
+    fn emit_event<S: Serialize + Clone>(&self, event: &str, payload: S) -> anyhow::Result<()>;
+}
+
+impl Emitter for AppHandle {
+    fn emit_event<S: Serialize + Clone>(&self, event: &str, payload: S) -> anyhow::Result<()> {
+        self.emit_all(event, payload)?;
+        Ok(())
+    }
+}
+
+/// Records emitted events so tests can assert on them.
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingEmitter(std::sync::Mutex<Vec<(String, serde_json::Value)>>);
+
+#[cfg(test)]
+impl RecordingEmitter {
+    pub fn payloads(&self, event: &str) -> Vec<serde_json::Value> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| name == event)
+            .map(|(_, payload)| payload.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl Emitter for RecordingEmitter {
+    fn emit_event<S: Serialize + Clone>(&self, event: &str, payload: S) -> anyhow::Result<()> {
+        let payload = serde_json::to_value(payload)?;
+        self.0.lock().unwrap().push((event.to_string(), payload));
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{
+    emitter::Emitter,
+    lcu_api::LcuApi,
+    lobby_history::{GameResult, LobbyHistory},
+};
+use serde::Deserialize;
+use std::{collections::HashMap, time::Duration};
+
+/// Backoff between reads of the end-of-game stats, doubling up to the last.
+const STATS_FIRST_RETRY: Duration = Duration::from_millis(500);
+const STATS_LAST_RETRY: Duration = Duration::from_secs(8);
+
+/// The parts of `/lol-end-of-game/v1/eog-stats-block` Reveal reads.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndOfGameStats {
+    pub game_id: u64,
+    /// In seconds.
+    #[serde(default)]
+    pub game_length: u64,
+    pub local_player: EndOfGamePlayer,
+    #[serde(default)]
+    pub teams: Vec<EndOfGameTeam>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndOfGamePlayer {
+    #[serde(default)]
+    pub champion_id: u32,
+    #[serde(default)]
+    pub champion_name: String,
+    /// Stat counters keyed by the client's names, e.g. `CHAMPIONS_KILLED`.
+    #[serde(default)]
+    pub stats: HashMap<String, i64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndOfGameTeam {
+    #[serde(default)]
+    pub is_player_team: bool,
+    #[serde(default)]
+    pub is_winning_team: bool,
+}
+
+impl EndOfGameStats {
+    pub fn result(&self) -> GameResult {
+        let stat = |name: &str| {
+            self.local_player
+                .stats
+                .get(name)
+                .copied()
+                .unwrap_or_default()
+                .max(0) as u32
+        };
+        let won = match self.teams.iter().find(|team| team.is_player_team) {
+            Some(team) => team.is_winning_team,
+            None => stat("WIN") > 0,
+        };
+
+        GameResult {
+            won,
+            champion_id: self.local_player.champion_id,
+            champion_name: self.local_player.champion_name.clone(),
+            kills: stat("CHAMPIONS_KILLED"),
+            deaths: stat("NUM_DEATHS"),
+            assists: stat("ASSISTS"),
+            game_length_seconds: self.game_length,
+        }
+    }
+}
+
+/// Attaches the result of the game that just ended to its history entry.
+pub async fn record_game_result(lcu: &impl LcuApi, history: &LobbyHistory, emitter: &impl Emitter) {
+    let stats = end_of_game_stats(lcu).await;
+    let result = stats.result();
+    log_info!(
+        "Game {} ended in a {} after {}s",
+        stats.game_id,
+        if result.won { "win" } else { "loss" },
+        result.game_length_seconds
+    );
+
+    match history.set_result(stats.game_id, result).await {
+        Ok(true) => {
+            if let Err(error) = emitter.emit_event("lobby_history_updated", stats.game_id) {
+                log_error!("Failed to emit lobby history update: {error}");
+            }
+        }
+        Ok(false) => log_info!("Game {} has no lobby history entry", stats.game_id),
+        Err(error) => log_error!("Failed to record the game result: {error:#}"),
+    }
+}
+
+/// Reads the end-of-game stats, retrying until the client has them. They are
+/// often published after the phase changes, and the phase task is cancelled
+/// if the phase ends first.
+async fn end_of_game_stats(lcu: &impl LcuApi) -> EndOfGameStats {
+    let mut retry_in = STATS_FIRST_RETRY;
+    loop {
+        match lcu.end_of_game_stats().await {
+            Ok(stats) => return stats,
+            Err(error) => {
+                log_debug!(
+                    "End-of-game stats are not available yet; retrying in {retry_in:?}: {error}"
+                );
+            }
+        }
+        tokio::time::sleep(retry_in).await;
+        retry_in = (retry_in * 2).min(STATS_LAST_RETRY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        emitter::RecordingEmitter,
+        lcu_api::fake::FakeLcuApi,
+        lobby::{Lobby, Participant},
+    };
+    use std::sync::Arc;
+
+    fn stats() -> EndOfGameStats {
+        serde_json::from_str(include_str!("../fixtures/end-of-game/eog-stats-block.json")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn attaches_the_result_to_the_games_history_entry() {
+        let lcu = FakeLcuApi::default();
+        lcu.state().end_of_game = Some(stats());
+        let history = LobbyHistory::temporary();
+        let lobby = Lobby {
+            participants: vec![Participant::from_riot_id("Duo", "NA1")],
+        };
+        history.record(5120937461, 420, "NA", &lobby).await.unwrap();
+        history.record(1, 420, "NA", &lobby).await.unwrap();
+        let emitter = RecordingEmitter::default();
+
+        record_game_result(&lcu, &history, &emitter).await;
+
+        let entries = history.list().await;
+        assert_eq!(entries[0].result, None);
+        assert_eq!(
+            entries[1].result,
+            Some(GameResult {
+                won: true,
+                champion_id: 64,
+                champion_name: "Lee Sin".to_string(),
+                kills: 5,
+                deaths: 3,
+                assists: 10,
+                game_length_seconds: 1834,
+            })
+        );
+        assert_eq!(
+            emitter.payloads("lobby_history_updated"),
+            [serde_json::json!(5120937461u64)]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_until_the_stats_are_published() {
+        let lcu = FakeLcuApi::default();
+        let history = LobbyHistory::temporary();
+        let lobby = Lobby {
+            participants: vec![Participant::from_riot_id("Duo", "NA1")],
+        };
+        history.record(5120937461, 420, "NA", &lobby).await.unwrap();
+        let emitter = Arc::new(RecordingEmitter::default());
+        let task = tokio::spawn({
+            let (lcu, history, emitter) = (lcu.clone(), history.clone(), emitter.clone());
+            async move { record_game_result(&lcu, &history, emitter.as_ref()).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!task.is_finished());
+        lcu.state().end_of_game = Some(stats());
+        task.await.unwrap();
+
+        assert!(history.list().await[0].result.is_some());
+        assert_eq!(
+            emitter.payloads("lobby_history_updated"),
+            [serde_json::json!(5120937461u64)]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_win_stat_without_team_data() {
+        let mut stats = stats();
+        stats.teams.clear();
+        assert!(stats.result().won);
+
+        stats.local_player.stats.insert("WIN".to_string(), 0);
+        assert!(!stats.result().won);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{gameflow::GameflowPhase, websocket::LcuEvent};
+use std::{collections::HashMap, sync::Mutex};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+pub const GAMEFLOW_PHASE_URI: &str = "/lol-gameflow/v1/gameflow-phase";
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Fans League and Riot Client events out to the features interested in them.
+///
+/// One bus lives for one client connection. Features register the URIs they
+/// need before the websockets subscribe, and every receiver sees the bus close
+/// when the connection ends.
+pub struct EventBus(Mutex<Channels>);
+
+struct Channels {
+    topics: HashMap<String, broadcast::Sender<LcuEvent>>,
+    phases: Option<broadcast::Sender<PhaseChange>>,
+    phase: GameflowPhase,
+}
+
+/// A transition between two gameflow phases.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhaseChange {
+    pub previous: GameflowPhase,
+    pub current: GameflowPhase,
+}
+
+impl PhaseChange {
+    pub fn entered(&self, phase: &GameflowPhase) -> bool {
+        self.current == *phase && self.previous != *phase
+    }
+
+    pub fn left(&self, phase: &GameflowPhase) -> bool {
+        self.previous == *phase && self.current != *phase
+    }
+}
+
+/// Phase changes that enter or leave the phases a feature registered for.
+pub struct PhaseReceiver {
+    receiver: broadcast::Receiver<PhaseChange>,
+    interest: Vec<GameflowPhase>,
+}
+
+impl PhaseReceiver {
+    /// Waits for the next relevant change, or `None` once the bus is closed.
+    pub async fn next(&mut self) -> Option<PhaseChange> {
+        loop {
+            let change = next(&mut self.receiver).await?;
+            if self.interest.is_empty()
+                || self
+                    .interest
+                    .iter()
+                    .any(|phase| change.entered(phase) || change.left(phase))
+            {
+                return Some(change);
+            }
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (phases, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let mut topics = HashMap::new();
+        topics.insert(
+            GAMEFLOW_PHASE_URI.to_string(),
+            broadcast::channel(CHANNEL_CAPACITY).0,
+        );
+
+        Self(Mutex::new(Channels {
+            topics,
+            phases: Some(phases),
+            phase: GameflowPhase::None,
+        }))
+    }
+}
+
+impl EventBus {
+    /// Declares interest in `uri` so the websockets subscribe to it, without
+    /// receiving its events yet.
+    pub fn register_uri(&self, uri: &str) {
+        self.subscribe(uri);
+    }
+
+    /// Receives every event published for `uri`.
+    pub fn subscribe(&self, uri: &str) -> broadcast::Receiver<LcuEvent> {
+        let mut channels = self.0.lock().unwrap();
+        if channels.phases.is_none() {
+            // Closed buses hand out receivers that end immediately
+            return broadcast::channel(1).1;
+        }
+
+        channels
+            .topics
+            .entry(uri.to_string())
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Receives phase changes that enter or leave one of `interest`, or every
+    /// change when `interest` is empty.
+    pub fn subscribe_phases(&self, interest: &[GameflowPhase]) -> PhaseReceiver {
+        let channels = self.0.lock().unwrap();
+        let receiver = match &channels.phases {
+            Some(phases) => phases.subscribe(),
+            None => broadcast::channel(1).1,
+        };
+
+        PhaseReceiver {
+            receiver,
+            interest: interest.to_vec(),
+        }
+    }
+
+    /// Every URI a feature has registered for.
+    pub fn uris(&self) -> Vec<String> {
+        let mut uris: Vec<_> = self.0.lock().unwrap().topics.keys().cloned().collect();
+        uris.sort();
+        uris
+    }
+
+    /// Delivers a websocket event to its subscribers. Gameflow phase events
+    /// are also published as phase changes.
+    pub fn publish(&self, event: LcuEvent) {
+        if event.uri == GAMEFLOW_PHASE_URI && event.event_type != "Delete" {
+            match serde_json::from_value::<GameflowPhase>(event.data.clone()) {
+                Ok(phase) => self.publish_phase(phase),
+                Err(error) => log_warn!("Failed to parse gameflow phase: {error}"),
+            }
+        }
+
+        let channels = self.0.lock().unwrap();
+        if let Some(topic) = channels.topics.get(&event.uri) {
+            // Sending only fails when nobody is currently listening.
+            let _ = topic.send(event);
+        }
+    }
+
+    /// Publishes `phase` if it differs from the current one.
+    pub fn publish_phase(&self, phase: GameflowPhase) {
+        let mut channels = self.0.lock().unwrap();
+        if channels.phase == phase {
+            return;
+        }
+
+        let change = PhaseChange {
+            previous: std::mem::replace(&mut channels.phase, phase.clone()),
+            current: phase,
+        };
+        if let Some(phases) = &channels.phases {
+            let _ = phases.send(change);
+        }
+    }
+
+    /// Ends every subscription, signalling that the client disconnected.
+    pub fn close(&self) {
+        let mut channels = self.0.lock().unwrap();
+        channels.topics.clear();
+        channels.phases = None;
+    }
+}
+
+/// Receives the next message, skipping any the receiver fell too far behind
+/// to see. Returns `None` once the bus is closed.
+pub async fn next<T: Clone>(receiver: &mut broadcast::Receiver<T>) -> Option<T> {
+    loop {
+        match receiver.recv().await {
+            Ok(message) => return Some(message),
+            Err(RecvError::Lagged(skipped)) => {
+                log_warn!("Event subscriber fell behind; skipped {skipped} events");
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(uri: &str, data: serde_json::Value) -> LcuEvent {
+        LcuEvent {
+            uri: uri.to_string(),
+            event_type: "Update".to_string(),
+            data,
+        }
+    }
+
+    #[tokio::test]
+    async fn delivers_events_to_subscribers_of_their_uri() {
+        let bus = EventBus::default();
+        let mut session = bus.subscribe("/lol-champ-select/v1/session");
+        bus.register_uri("/chat/v5/participants");
+
+        bus.publish(event("/chat/v5/participants", json!({})));
+        bus.publish(event(
+            "/lol-champ-select/v1/session",
+            json!({ "gameId": 1 }),
+        ));
+
+        let received = next(&mut session).await.unwrap();
+        assert_eq!(received.data["gameId"], 1);
+        assert!(session.try_recv().is_err());
+        assert_eq!(
+            bus.uris(),
+            [
+                "/chat/v5/participants",
+                "/lol-champ-select/v1/session",
+                GAMEFLOW_PHASE_URI
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn publishes_only_changes_in_registered_phases() {
+        let bus = EventBus::default();
+        let mut ready_check = bus.subscribe_phases(&[GameflowPhase::ReadyCheck]);
+        let mut every_phase = bus.subscribe_phases(&[]);
+
+        for phase in ["Lobby", "Lobby", "Matchmaking", "ReadyCheck", "ChampSelect"] {
+            bus.publish(event(GAMEFLOW_PHASE_URI, json!(phase)));
+        }
+        bus.close();
+
+        let mut changes = Vec::new();
+        while let Some(change) = ready_check.next().await {
+            changes.push(change.current);
+        }
+        assert_eq!(
+            changes,
+            [GameflowPhase::ReadyCheck, GameflowPhase::ChampSelect]
+        );
+
+        let mut changes = Vec::new();
+        while let Some(change) = every_phase.next().await {
+            changes.push(change.current);
+        }
+        assert_eq!(changes.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn closing_ends_current_and_later_subscriptions() {
+        let bus = EventBus::default();
+        let mut before = bus.subscribe("/lol-champ-select/v1/session");
+
+        bus.close();
+        let mut after = bus.subscribe("/lol-champ-select/v1/session");
+
+        assert!(next(&mut before).await.is_none());
+        assert!(next(&mut after).await.is_none());
+        assert!(bus.subscribe_phases(&[]).next().await.is_none());
+    }
+}
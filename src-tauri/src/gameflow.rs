@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Phase reported by `/lol-gameflow/v1/gameflow-phase`.
+///
+/// Serialized as the client's own phase name, so the frontend receives the
+/// same strings the League Client uses.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum GameflowPhase {
+    #[default]
+    None,
+    Lobby,
+    Matchmaking,
+    ReadyCheck,
+    ChampSelect,
+    GameStart,
+    InProgress,
+    Reconnect,
+    WaitingForStats,
+    PreEndOfGame,
+    EndOfGame,
+    /// A phase this version of Reveal does not know about.
+    Unknown(String),
+}
+
+/// The parts of `/lol-gameflow/v1/session` Reveal reads.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameflowSession {
+    #[serde(default)]
+    pub game_data: GameData,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameData {
+    #[serde(default)]
+    pub game_id: u64,
+    #[serde(default)]
+    pub queue: Queue,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Queue {
+    /// `-1` for custom games.
+    pub id: i64,
+}
+
+impl GameflowPhase {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::None => "None",
+            Self::Lobby => "Lobby",
+            Self::Matchmaking => "Matchmaking",
+            Self::ReadyCheck => "ReadyCheck",
+            Self::ChampSelect => "ChampSelect",
+            Self::GameStart => "GameStart",
+            Self::InProgress => "InProgress",
+            Self::Reconnect => "Reconnect",
+            Self::WaitingForStats => "WaitingForStats",
+            Self::PreEndOfGame => "PreEndOfGame",
+            Self::EndOfGame => "EndOfGame",
+            Self::Unknown(phase) => phase,
+        }
+    }
+}
+
+impl From<String> for GameflowPhase {
+    fn from(phase: String) -> Self {
+        match phase.as_str() {
+            "None" => Self::None,
+            "Lobby" => Self::Lobby,
+            "Matchmaking" => Self::Matchmaking,
+            "ReadyCheck" => Self::ReadyCheck,
+            "ChampSelect" => Self::ChampSelect,
+            "GameStart" => Self::GameStart,
+            "InProgress" => Self::InProgress,
+            "Reconnect" => Self::Reconnect,
+            "WaitingForStats" => Self::WaitingForStats,
+            "PreEndOfGame" => Self::PreEndOfGame,
+            "EndOfGame" => Self::EndOfGame,
+            _ => Self::Unknown(phase),
+        }
+    }
+}
+
+impl From<GameflowPhase> for String {
+    fn from(phase: GameflowPhase) -> Self {
+        match phase {
+            GameflowPhase::Unknown(phase) => phase,
+            phase => phase.as_str().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for GameflowPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_known_phase() {
+        for phase in [
+            "None",
+            "Lobby",
+            "Matchmaking",
+            "ReadyCheck",
+            "ChampSelect",
+            "GameStart",
+            "InProgress",
+            "Reconnect",
+            "WaitingForStats",
+            "PreEndOfGame",
+            "EndOfGame",
+        ] {
+            let parsed: GameflowPhase = serde_json::from_value(phase.into()).unwrap();
+            assert!(!matches!(parsed, GameflowPhase::Unknown(_)), "{phase}");
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), phase);
+        }
+    }
+
+    #[test]
+    fn keeps_unknown_phases_verbatim() {
+        let parsed: GameflowPhase = serde_json::from_str(r#""TerminatedInError""#).unwrap();
+
+        assert_eq!(
+            parsed,
+            GameflowPhase::Unknown("TerminatedInError".to_string())
+        );
+        assert_eq!(
+            serde_json::to_string(&parsed).unwrap(),
+            r#""TerminatedInError""#
+        );
+    }
+}
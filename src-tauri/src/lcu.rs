@@ -1,23 +1,26 @@
-use crate::app_state::{Dodge, Lcu};
-use crate::champ_select::ChampSelectSession;
-use crate::state;
-use futures_util::StreamExt;
-use shaco::model::ws::{LcuEvent, LcuSubscriptionType::JsonApiEvent};
-use shaco::rest::RESTClient;
+use crate::app_state::Lcu;
+use crate::event_bus::EventBus;
+use crate::lcu_api::{LcuApi, ShacoLcuApi};
+use crate::state::{self, PhaseContext};
+use crate::websocket::{LcuEvent, LcuWebsocket};
+use crate::{champ_select, dodge, logging, presets, profiles};
 use shaco::utils::process_info;
-use shaco::ws::LcuWebsocketClient;
+use std::sync::Arc;
 use std::time::Duration;
 use tauri::{AppHandle, Manager};
 
 const CLIENT_POLL_INTERVAL: Duration = Duration::from_secs(2);
 const WEBSOCKET_RETRY_INTERVAL: Duration = Duration::from_secs(3);
 const WEBSOCKET_ATTEMPTS: usize = 7;
+const LCU_PORT_OVERRIDE: &str = "REVEAL_LCU_PORT";
+const LCU_TOKEN_OVERRIDE: &str = "REVEAL_LCU_TOKEN";
 
 pub async fn run(app_handle: AppHandle) {
     let mut was_connected = true;
+    let mut connections = 0;
 
     loop {
-        let Some(args) = process_info::get_league_process_args() else {
+        let Some(args) = league_process_args() else {
             if was_connected {
                 log_info!("League Client is not running; waiting for it to open");
                 was_connected = false;
@@ -28,7 +31,18 @@ pub async fn run(app_handle: AppHandle) {
             continue;
         };
 
-        let lcu_info = match process_info::get_auth_info(args) {
+        let Some(websocket_port) =
+            process_arg(&args, "--app-port").and_then(|port| port.parse().ok())
+        else {
+            log_warn!("League Client arguments are missing the app port");
+            tokio::time::sleep(CLIENT_POLL_INTERVAL).await;
+            continue;
+        };
+        let websocket_token = process_arg(&args, "--remoting-auth-token")
+            .unwrap_or_default()
+            .to_string();
+
+        let lcu_info = match process_info::get_auth_info(args.clone()) {
             Ok(info) => info,
             Err(error) => {
                 log_warn!("Failed to read League Client connection info: {error}");
@@ -37,89 +51,182 @@ pub async fn run(app_handle: AppHandle) {
             }
         };
 
-        let Some(app_client) = create_rest_client(lcu_info.clone(), false) else {
-            tokio::time::sleep(CLIENT_POLL_INTERVAL).await;
-            continue;
-        };
-        let Some(remoting_client) = create_rest_client(lcu_info.clone(), true) else {
-            tokio::time::sleep(CLIENT_POLL_INTERVAL).await;
-            continue;
+        let lcu = match ShacoLcuApi::new(lcu_info.clone()) {
+            Ok(lcu) => lcu,
+            Err(error) => {
+                log_warn!("Failed to create League Client API client: {error}");
+                tokio::time::sleep(CLIENT_POLL_INTERVAL).await;
+                continue;
+            }
         };
 
         was_connected = true;
         set_connection_state(&app_handle, true, Some(lcu_info)).await;
 
-        let Some(mut websocket) = connect_websocket().await else {
-            log_error!("League Client websocket retries were exhausted");
-            tokio::time::sleep(CLIENT_POLL_INTERVAL).await;
-            continue;
-        };
+        connections += 1;
+        logging::scoped(
+            format!("lcu-{connections}"),
+            run_connection(&app_handle, &lcu, &args, websocket_port, &websocket_token),
+        )
+        .await;
+    }
+}
 
-        if let Err(error) = websocket
-            .subscribe(JsonApiEvent("/lol-gameflow/v1/gameflow-phase".to_string()))
-            .await
-        {
-            log_error!("Failed to subscribe to gameflow updates: {error}");
-            continue;
-        }
+/// Talks to one League Client until its websocket closes. Everything logged
+/// in the meantime carries the connection as its correlation.
+async fn run_connection(
+    context: &impl PhaseContext,
+    lcu: &ShacoLcuApi,
+    args: &[String],
+    websocket_port: u16,
+    websocket_token: &str,
+) {
+    let Some(mut websocket) = connect_websocket(websocket_port, websocket_token).await else {
+        log_error!("League Client websocket retries were exhausted");
+        tokio::time::sleep(CLIENT_POLL_INTERVAL).await;
+        return;
+    };
 
-        if let Err(error) = websocket
-            .subscribe(JsonApiEvent("/lol-champ-select/v1/session".to_string()))
-            .await
-        {
-            log_error!("Failed to subscribe to Champ Select updates: {error}");
-            continue;
-        }
+    let bus = Arc::new(EventBus::default());
+    register_features(&bus, context, lcu);
+
+    // Chat is served by the Riot Client, which has its own websocket.
+    // Without it, features fall back to polling those resources.
+    let mut riot_websocket = connect_riot_websocket(args).await;
+    if let Err(error) = subscribe(&bus, &mut websocket, &mut riot_websocket).await {
+        log_error!("Failed to subscribe to League Client events: {error}");
+        bus.close();
+        return;
+    }
+
+    log_info!("Connected to League Client");
+
+    // Before the first phase is published, so features start with the
+    // right profile's settings. Until someone logs in, the account comes
+    // from the current summoner updates instead.
+    if let Err(error) =
+        profiles::switch_account(lcu, context.app_config(), context.account(), context).await
+    {
+        log_info!("No account is logged in yet: {error:#}");
+    }
+
+    match lcu.gameflow_phase().await {
+        Ok(client_state) => bus.publish_phase(client_state),
+        Err(error) => log_warn!("Failed to read initial gameflow state: {error}"),
+    }
 
-        log_info!("Connected to League Client");
-
-        match state::get_gameflow_state(&remoting_client).await {
-            Ok(client_state) => {
-                state::handle_client_state(
-                    client_state,
-                    &app_handle,
-                    &remoting_client,
-                    &app_client,
-                )
-                .await;
+    pump_events(&bus, &mut websocket, riot_websocket).await;
+    log_warn!("League Client websocket closed; reconnecting");
+    bus.close();
+    profiles::forget_account(context.app_config(), context.account(), context).await;
+}
+
+/// Starts every feature that reacts to client events. Each one registers the
+/// URIs and phases it needs on the bus and runs until the bus closes.
+fn register_features(bus: &Arc<EventBus>, context: &impl PhaseContext, lcu: &ShacoLcuApi) {
+    state::register(bus, context, lcu);
+    champ_select::register(bus, context.clone());
+    dodge::register(bus, context, lcu);
+    presets::register(bus, context, lcu);
+    profiles::register(bus, context);
+}
+
+/// Subscribes the websockets to every URI registered on the bus. The Riot
+/// Client websocket is dropped if it rejects a subscription.
+async fn subscribe(
+    bus: &EventBus,
+    websocket: &mut LcuWebsocket,
+    riot_websocket: &mut Option<LcuWebsocket>,
+) -> anyhow::Result<()> {
+    for uri in bus.uris() {
+        if !served_by_riot_client(&uri) {
+            websocket.subscribe(&uri).await?;
+        } else if let Some(connected) = riot_websocket.as_mut() {
+            if let Err(error) = connected.subscribe(&uri).await {
+                log_warn!("Failed to subscribe to Riot Client updates for {uri}: {error}");
+                *riot_websocket = None;
             }
-            Err(error) => log_warn!("Failed to read initial gameflow state: {error}"),
         }
+    }
 
-        while let Some(message) = websocket.next().await {
-            handle_websocket_message(message, &app_handle, &remoting_client, &app_client).await;
-        }
-        log_warn!("League Client websocket closed; reconnecting");
+    Ok(())
+}
+
+fn served_by_riot_client(uri: &str) -> bool {
+    uri.starts_with("/chat/")
+}
+
+/// Publishes websocket events on the bus until the League websocket closes.
+async fn pump_events(
+    bus: &EventBus,
+    websocket: &mut LcuWebsocket,
+    mut riot_websocket: Option<LcuWebsocket>,
+) {
+    loop {
+        let message = tokio::select! {
+            message = websocket.next_event() => match message {
+                Some(message) => message,
+                None => return,
+            },
+            message = next_optional_event(&mut riot_websocket) => match message {
+                Some(message) => message,
+                None => {
+                    log_warn!("Riot Client websocket closed; chat updates will fall back to polling");
+                    riot_websocket = None;
+                    continue;
+                }
+            },
+        };
+        bus.publish(message);
     }
 }
 
-fn create_rest_client(lcu_info: shaco::rest::LCUClientInfo, remoting: bool) -> Option<RESTClient> {
-    match RESTClient::new(lcu_info, remoting) {
-        Ok(client) => Some(client),
-        Err(error) => {
-            log_warn!("Failed to create League Client API client: {error}");
-            None
-        }
+/// Returns the League Client's command-line arguments.
+///
+/// Setting `REVEAL_LCU_PORT` and `REVEAL_LCU_TOKEN` points Reveal at another
+/// client, such as the mock LCU, instead of the running League process.
+fn league_process_args() -> Option<Vec<String>> {
+    let port = std::env::var(LCU_PORT_OVERRIDE).ok();
+    let token = std::env::var(LCU_TOKEN_OVERRIDE).ok();
+    match port.zip(token) {
+        Some((port, token)) => Some(override_process_args(&port, &token)),
+        None => process_info::get_league_process_args(),
     }
 }
 
-async fn connect_websocket() -> Option<LcuWebsocketClient> {
+/// Mirrors the arguments League passes to its own process, serving both the
+/// League and Riot Client APIs from a single port.
+fn override_process_args(port: &str, token: &str) -> Vec<String> {
+    vec![
+        format!("--app-port={port}"),
+        format!("--remoting-auth-token={token}"),
+        format!("--riotclient-app-port={port}"),
+        format!("--riotclient-auth-token={token}"),
+    ]
+}
+
+fn process_arg<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .find_map(|arg| arg.strip_prefix(name)?.strip_prefix('='))
+}
+
+async fn connect_websocket(port: u16, auth_token: &str) -> Option<LcuWebsocket> {
     for attempt in 1..=WEBSOCKET_ATTEMPTS {
         if attempt > 1 {
             tokio::time::sleep(WEBSOCKET_RETRY_INTERVAL).await;
         }
 
-        match LcuWebsocketClient::connect().await {
+        match LcuWebsocket::connect(port, auth_token).await {
             Ok(websocket) => {
                 log_info!("League Client websocket connected on attempt {attempt}");
                 return Some(websocket);
             }
             Err(error) if attempt == WEBSOCKET_ATTEMPTS => {
-                log_error!("League websocket connection failed: {error}");
+                log_error!("League websocket connection failed: {error:#}");
             }
             Err(error) => {
                 log_warn!(
-                    "League websocket connection attempt {attempt}/{WEBSOCKET_ATTEMPTS} failed: {error}"
+                    "League websocket connection attempt {attempt}/{WEBSOCKET_ATTEMPTS} failed: {error:#}"
                 );
             }
         }
@@ -128,6 +235,30 @@ async fn connect_websocket() -> Option<LcuWebsocketClient> {
     None
 }
 
+async fn connect_riot_websocket(args: &[String]) -> Option<LcuWebsocket> {
+    let port = process_arg(args, "--riotclient-app-port")?.parse().ok()?;
+    let auth_token = process_arg(args, "--riotclient-auth-token")?;
+
+    match LcuWebsocket::connect(port, auth_token).await {
+        Ok(websocket) => {
+            log_info!("Riot Client websocket connected");
+            Some(websocket)
+        }
+        Err(error) => {
+            log_warn!("Riot Client websocket connection failed: {error:#}");
+            None
+        }
+    }
+}
+
+/// Waits for the next event, or forever if there is no websocket.
+async fn next_optional_event(websocket: &mut Option<LcuWebsocket>) -> Option<LcuEvent> {
+    match websocket {
+        Some(websocket) => websocket.next_event().await,
+        None => std::future::pending().await,
+    }
+}
+
 async fn set_connection_state(
     app_handle: &AppHandle,
     connected: bool,
@@ -144,68 +275,233 @@ async fn set_connection_state(
     }
 }
 
-async fn handle_websocket_message(
-    message: LcuEvent,
-    app_handle: &AppHandle,
-    remoting_client: &RESTClient,
-    app_client: &RESTClient,
-) {
-    let message_type = message.subscription_type.to_string();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        event_bus::GAMEFLOW_PHASE_URI,
+        gameflow::GameflowPhase,
+        lobby::{self, Lobby},
+        mock_lcu::{MockLcu, Timeline},
+        state::fake::{eventually, TestContext},
+    };
 
-    match message_type.as_str() {
-        "OnJsonApiEvent_lol-gameflow_v1_gameflow-phase" => {
-            let client_state = message.data.to_string().replace('"', "");
-            state::handle_client_state(client_state, app_handle, remoting_client, app_client).await;
-        }
-        "OnJsonApiEvent_lol-champ-select_v1_session" => {
-            handle_last_second_dodge(message, app_handle, remoting_client).await;
-        }
-        _ => log_warn!("Unhandled League websocket message: {message_type}"),
+    const TEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn recorded_timeline_with_delays() -> Timeline {
+        Timeline::from_json(include_str!("../fixtures/mock-lcu/champ_select.json")).unwrap()
     }
-}
 
-async fn handle_last_second_dodge(
-    message: LcuEvent,
-    app_handle: &AppHandle,
-    remoting_client: &RESTClient,
-) {
-    let champ_select = match serde_json::from_value::<ChampSelectSession>(message.data) {
-        Ok(session) => session,
-        Err(error) => {
-            log_warn!("Failed to parse Champ Select session: {error}");
-            return;
+    fn recorded_timeline() -> Timeline {
+        let mut timeline = recorded_timeline_with_delays();
+        for step in &mut timeline.steps {
+            step.delay_ms = 0;
         }
-    };
+        timeline
+    }
 
-    if champ_select.timer.phase != "FINALIZATION" {
-        return;
+    #[test]
+    fn override_arguments_expose_the_port_and_token() {
+        let args = override_process_args("50123", "secret");
+
+        assert_eq!(process_arg(&args, "--app-port"), Some("50123"));
+        assert_eq!(process_arg(&args, "--remoting-auth-token"), Some("secret"));
+        assert_eq!(process_arg(&args, "--app"), None);
     }
 
-    let game_id = champ_select.game_id;
-    let dodge = app_handle.state::<Dodge>();
-    let mut dodge = dodge.0.lock().await;
-    if dodge.last_dodge == Some(game_id) || dodge.enabled != Some(game_id) {
-        return;
+    #[tokio::test]
+    async fn publishes_a_recorded_timeline_on_the_event_bus() {
+        let mock = MockLcu::start().await.unwrap();
+        let bus = EventBus::default();
+        let mut phases = bus.subscribe_phases(&[]);
+        bus.register_uri("/lol-champ-select/v1/session");
+        let mut websocket = LcuWebsocket::connect(mock.port(), mock.auth_token())
+            .await
+            .unwrap();
+        let mut riot_websocket = Some(
+            LcuWebsocket::connect(mock.port(), mock.auth_token())
+                .await
+                .unwrap(),
+        );
+        subscribe(&bus, &mut websocket, &mut riot_websocket)
+            .await
+            .unwrap();
+        mock.wait_for_subscription(GAMEFLOW_PHASE_URI)
+            .await
+            .unwrap();
+
+        let pump = pump_events(&bus, &mut websocket, riot_websocket);
+        let collect = async {
+            mock.replay(&recorded_timeline()).await;
+            let mut seen = Vec::new();
+            while seen.last() != Some(&GameflowPhase::GameStart) {
+                seen.push(phases.next().await.unwrap().current);
+            }
+            seen
+        };
+        let seen = tokio::time::timeout(TEST_TIMEOUT, async {
+            tokio::select! {
+                _ = pump => panic!("the websocket closed early"),
+                seen = collect => seen,
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            seen,
+            [
+                GameflowPhase::ReadyCheck,
+                GameflowPhase::ChampSelect,
+                GameflowPhase::GameStart
+            ]
+        );
     }
 
-    dodge.last_dodge = Some(game_id);
-    drop(dodge);
+    #[tokio::test]
+    async fn reveals_a_recorded_champ_select_end_to_end() {
+        let mock = MockLcu::start().await.unwrap();
+        std::env::set_var(LCU_PORT_OVERRIDE, mock.port().to_string());
+        std::env::set_var(LCU_TOKEN_OVERRIDE, mock.auth_token());
+        let args = league_process_args().unwrap();
+        let websocket_port = process_arg(&args, "--app-port").unwrap().parse().unwrap();
+        let websocket_token = process_arg(&args, "--remoting-auth-token").unwrap();
+        let lcu = ShacoLcuApi::new(process_info::get_auth_info(args.clone()).unwrap()).unwrap();
+        let context = TestContext::new(Config {
+            auto_open: false,
+            ..Config::default()
+        });
+
+        // Keep the recorded pacing, ten times faster, so the tracker sees
+        // Champ Select before the game starts.
+        let mut timeline = recorded_timeline_with_delays();
+        for step in &mut timeline.steps {
+            step.delay_ms /= 10;
+        }
+        let connection = run_connection(&context, &lcu, &args, websocket_port, websocket_token);
+        let replay = async {
+            for uri in [GAMEFLOW_PHASE_URI, lobby::CHAT_PARTICIPANTS_URI] {
+                mock.wait_for_subscription(uri).await.unwrap();
+            }
+            mock.replay(&timeline).await;
+            // Game start cancels the Champ Select tasks once they are done.
+            eventually(|| {
+                !context
+                    .emitter()
+                    .payloads("lobby_history_updated")
+                    .is_empty()
+                    && context.supervisor().running_tasks().is_empty()
+            })
+            .await;
+        };
+        tokio::time::timeout(TEST_TIMEOUT, async {
+            tokio::select! {
+                _ = connection => panic!("the websocket closed early"),
+                _ = replay => {}
+            }
+        })
+        .await
+        .unwrap();
 
-    let delay = Duration::from_millis(champ_select.timer.adjusted_time_left_in_phase);
-    let remoting_client = remoting_client.clone();
-    log_warn!("Last-second dodge scheduled after {delay:?}");
+        let started = context.emitter().payloads("champ_select_started");
+        let team = &started.last().unwrap()["participants"];
+        let names: Vec<_> = team
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|participant| participant["game_name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names.len(), 5);
+        assert_eq!(names[0], "Ahri Enjoyer");
+        assert_eq!(team[0]["assigned_position"], "jungle");
+        let recorded = context.emitter().payloads("lobby_history_updated");
+        assert!(recorded
+            .iter()
+            .all(|game_id| *game_id == serde_json::json!(4_821_337_001_u64)));
+        let history = context.records().history.list().await;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].participants.len(), 5);
+    }
 
-    tauri::async_runtime::spawn(async move {
-        tokio::time::sleep(delay).await;
-        log_warn!("Calling the last-second dodge endpoint");
-        if let Err(error) = remoting_client
-            .post(
-                "/lol-login/v1/session/invoke?destination=lcdsServiceProxy&method=call&args=[\"\",\"teambuilder-draft\",\"quitV2\",\"\"]".to_string(),
-                serde_json::json!({}),
-            )
+    #[tokio::test]
+    async fn streams_a_recorded_timeline_from_the_mock_lcu() {
+        let mock = MockLcu::start().await.unwrap();
+        let mut websocket = LcuWebsocket::connect(mock.port(), mock.auth_token())
             .await
-        {
-            log_error!("Last-second dodge failed: {error}");
+            .unwrap();
+        websocket
+            .subscribe("/lol-gameflow/v1/gameflow-phase")
+            .await
+            .unwrap();
+        mock.wait_for_subscription("/lol-gameflow/v1/gameflow-phase")
+            .await
+            .unwrap();
+
+        mock.replay(&recorded_timeline()).await;
+
+        let mut phases = Vec::new();
+        while phases.last() != Some(&GameflowPhase::GameStart) {
+            let event = tokio::time::timeout(TEST_TIMEOUT, websocket.next_event())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(event.uri, "/lol-gameflow/v1/gameflow-phase");
+            phases.push(serde_json::from_value::<GameflowPhase>(event.data).unwrap());
         }
-    });
+        assert_eq!(
+            phases,
+            [
+                GameflowPhase::ReadyCheck,
+                GameflowPhase::ChampSelect,
+                GameflowPhase::GameStart
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn serves_rest_resources_and_records_actions() {
+        let mock = MockLcu::start().await.unwrap();
+        mock.replay(&recorded_timeline()).await;
+        let base_url = format!("https://127.0.0.1:{}", mock.port());
+        let client = mock.client();
+
+        let lobby: Lobby = client
+            .get(format!("{base_url}/chat/v5/participants"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(lobby.participants.len(), 5);
+
+        let session = client
+            .get(format!("{base_url}/lol-champ-select/v1/session"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(session.status(), reqwest::StatusCode::NOT_FOUND);
+
+        client
+            .post(format!("{base_url}/lol-matchmaking/v1/ready-check/accept"))
+            .json(&serde_json::json!({}))
+            .send()
+            .await
+            .unwrap();
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/lol-matchmaking/v1/ready-check/accept");
+
+        let unauthorized = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .unwrap()
+            .get(format!("{base_url}/lol-gameflow/v1/gameflow-phase"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(unauthorized.status(), reqwest::StatusCode::UNAUTHORIZED);
+    }
 }
@@ -0,0 +1,373 @@
+use crate::{
+    champ_select::ChampSelectSession,
+    end_of_game::EndOfGameStats,
+    gameflow::{GameflowPhase, GameflowSession},
+    lobby::Lobby,
+    match_history::{MatchHistory, RankedStats, RECENT_GAMES},
+    presets::{RunePage, RunePageSummary},
+    ready_check::ReadyCheck,
+    region::RegionInfo,
+    summoner::Summoner,
+};
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use shaco::rest::{LCUClientInfo, RESTClient};
+use std::future::Future;
+
+const QUIT_CHAMP_SELECT_PATH: &str = "/lol-login/v1/session/invoke?destination=lcdsServiceProxy&method=call&args=[\"\",\"teambuilder-draft\",\"quitV2\",\"\"]";
+
+/// Typed access to the League and Riot Client endpoints Reveal uses.
+pub trait LcuApi: Clone + Send + Sync + 'static {
+    fn gameflow_phase(&self) -> impl Future<Output = Result<GameflowPhase>> + Send;
+    fn gameflow_session(&self) -> impl Future<Output = Result<GameflowSession>> + Send;
+    fn chat_participants(&self) -> impl Future<Output = Result<Lobby>> + Send;
+    fn champ_select_session(&self) -> impl Future<Output = Result<ChampSelectSession>> + Send;
+    fn region_locale(&self) -> impl Future<Output = Result<RegionInfo>> + Send;
+    fn current_summoner(&self) -> impl Future<Output = Result<Summoner>> + Send;
+    fn ready_check(&self) -> impl Future<Output = Result<ReadyCheck>> + Send;
+    fn accept_ready_check(&self) -> impl Future<Output = Result<()>> + Send;
+    fn decline_ready_check(&self) -> impl Future<Output = Result<()>> + Send;
+    fn quit_champ_select(&self) -> impl Future<Output = Result<()>> + Send;
+    /// Hovers `champion_id` for a pick or ban, locking it in if `completed`.
+    fn patch_champ_select_action(
+        &self,
+        action_id: u64,
+        champion_id: u32,
+        completed: bool,
+    ) -> impl Future<Output = Result<()>> + Send;
+    fn set_summoner_spells(
+        &self,
+        spell1_id: u64,
+        spell2_id: u64,
+    ) -> impl Future<Output = Result<()>> + Send;
+    fn rune_pages(&self) -> impl Future<Output = Result<Vec<RunePageSummary>>> + Send;
+    fn delete_rune_page(&self, page_id: u64) -> impl Future<Output = Result<()>> + Send;
+    /// Creates `page` and makes it the current page.
+    fn create_rune_page(&self, page: &RunePage) -> impl Future<Output = Result<()>> + Send;
+    fn end_of_game_stats(&self) -> impl Future<Output = Result<EndOfGameStats>> + Send;
+    /// The player's [`RECENT_GAMES`] most recent games.
+    fn match_history(&self, puuid: &str) -> impl Future<Output = Result<MatchHistory>> + Send;
+    fn ranked_stats(&self, puuid: &str) -> impl Future<Output = Result<RankedStats>> + Send;
+}
+
+/// [`LcuApi`] backed by the running League Client.
+#[derive(Clone)]
+pub struct ShacoLcuApi {
+    /// Riot Client API, which serves chat and region data.
+    app_client: RESTClient,
+    /// League Client API.
+    remoting_client: RESTClient,
+}
+
+impl ShacoLcuApi {
+    pub fn new(lcu_info: LCUClientInfo) -> Result<Self> {
+        Ok(Self {
+            app_client: RESTClient::new(lcu_info.clone(), false)?,
+            remoting_client: RESTClient::new(lcu_info, true)?,
+        })
+    }
+}
+
+async fn get<T: DeserializeOwned>(client: &RESTClient, path: &str) -> Result<T> {
+    let value = client.get(path.to_string()).await?;
+    Ok(serde_json::from_value(value)?)
+}
+
+async fn post(client: &RESTClient, path: &str) -> Result<()> {
+    client.post(path.to_string(), serde_json::json!({})).await?;
+    Ok(())
+}
+
+impl LcuApi for ShacoLcuApi {
+    async fn gameflow_phase(&self) -> Result<GameflowPhase> {
+        get(&self.remoting_client, "/lol-gameflow/v1/gameflow-phase").await
+    }
+
+    async fn gameflow_session(&self) -> Result<GameflowSession> {
+        get(&self.remoting_client, "/lol-gameflow/v1/session").await
+    }
+
+    async fn chat_participants(&self) -> Result<Lobby> {
+        get(&self.app_client, "/chat/v5/participants").await
+    }
+
+    async fn champ_select_session(&self) -> Result<ChampSelectSession> {
+        get(&self.remoting_client, "/lol-champ-select/v1/session").await
+    }
+
+    async fn region_locale(&self) -> Result<RegionInfo> {
+        get(&self.app_client, "/riotclient/region-locale").await
+    }
+
+    async fn current_summoner(&self) -> Result<Summoner> {
+        get(&self.remoting_client, "/lol-summoner/v1/current-summoner").await
+    }
+
+    async fn ready_check(&self) -> Result<ReadyCheck> {
+        get(&self.remoting_client, "/lol-matchmaking/v1/ready-check").await
+    }
+
+    async fn accept_ready_check(&self) -> Result<()> {
+        post(
+            &self.remoting_client,
+            "/lol-matchmaking/v1/ready-check/accept",
+        )
+        .await
+    }
+
+    async fn decline_ready_check(&self) -> Result<()> {
+        post(
+            &self.remoting_client,
+            "/lol-matchmaking/v1/ready-check/decline",
+        )
+        .await
+    }
+
+    async fn quit_champ_select(&self) -> Result<()> {
+        post(&self.remoting_client, QUIT_CHAMP_SELECT_PATH).await
+    }
+
+    async fn patch_champ_select_action(
+        &self,
+        action_id: u64,
+        champion_id: u32,
+        completed: bool,
+    ) -> Result<()> {
+        self.remoting_client
+            .patch(
+                format!("/lol-champ-select/v1/session/actions/{action_id}"),
+                serde_json::json!({ "championId": champion_id, "completed": completed }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn set_summoner_spells(&self, spell1_id: u64, spell2_id: u64) -> Result<()> {
+        self.remoting_client
+            .patch(
+                "/lol-champ-select/v1/session/my-selection".to_string(),
+                serde_json::json!({ "spell1Id": spell1_id, "spell2Id": spell2_id }),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn rune_pages(&self) -> Result<Vec<RunePageSummary>> {
+        get(&self.remoting_client, "/lol-perks/v1/pages").await
+    }
+
+    async fn delete_rune_page(&self, page_id: u64) -> Result<()> {
+        self.remoting_client
+            .delete(format!("/lol-perks/v1/pages/{page_id}"))
+            .await?;
+        Ok(())
+    }
+
+    async fn create_rune_page(&self, page: &RunePage) -> Result<()> {
+        let mut body = serde_json::to_value(page)?;
+        body["current"] = true.into();
+        self.remoting_client
+            .post("/lol-perks/v1/pages".to_string(), body)
+            .await?;
+        Ok(())
+    }
+
+    async fn end_of_game_stats(&self) -> Result<EndOfGameStats> {
+        get(&self.remoting_client, "/lol-end-of-game/v1/eog-stats-block").await
+    }
+
+    async fn match_history(&self, puuid: &str) -> Result<MatchHistory> {
+        let path = format!(
+            "/lol-match-history/v1/products/lol/{puuid}/matches?begIndex=0&endIndex={}",
+            RECENT_GAMES - 1
+        );
+        get(&self.remoting_client, &path).await
+    }
+
+    async fn ranked_stats(&self, puuid: &str) -> Result<RankedStats> {
+        get(
+            &self.remoting_client,
+            &format!("/lol-ranked/v1/ranked-stats/{puuid}"),
+        )
+        .await
+    }
+}
+
+/// In-memory [`LcuApi`] for tests.
+#[cfg(test)]
+pub mod fake {
+    use super::*;
+    use anyhow::Context;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex, MutexGuard},
+    };
+
+    #[derive(Default)]
+    pub struct FakeState {
+        pub gameflow_phase: GameflowPhase,
+        pub queue_id: Option<i64>,
+        pub participants: Vec<crate::lobby::Participant>,
+        pub session: Option<ChampSelectSession>,
+        pub ready_check: Option<ReadyCheck>,
+        pub web_region: String,
+        /// Names of the actions Reveal performed, in order.
+        pub actions: Vec<&'static str>,
+        /// `(action_id, champion_id, completed)` for every draft action patch.
+        pub action_patches: Vec<(u64, u32, bool)>,
+        pub summoner_spells: Option<(u64, u64)>,
+        pub rune_pages: Vec<RunePageSummary>,
+        /// Pages created by Reveal, in order.
+        pub created_rune_pages: Vec<RunePage>,
+        pub end_of_game: Option<EndOfGameStats>,
+        /// Keyed by puuid.
+        pub match_histories: HashMap<String, MatchHistory>,
+        /// Keyed by puuid.
+        pub ranked_stats: HashMap<String, RankedStats>,
+        /// Defaults to `Local Player#NA1`.
+        pub summoner: Option<Summoner>,
+    }
+
+    #[derive(Clone, Default)]
+    pub struct FakeLcuApi(Arc<Mutex<FakeState>>);
+
+    impl FakeLcuApi {
+        pub fn state(&self) -> MutexGuard<'_, FakeState> {
+            self.0.lock().unwrap()
+        }
+
+        pub fn actions(&self) -> Vec<&'static str> {
+            self.state().actions.clone()
+        }
+    }
+
+    impl LcuApi for FakeLcuApi {
+        async fn gameflow_phase(&self) -> Result<GameflowPhase> {
+            Ok(self.state().gameflow_phase.clone())
+        }
+
+        async fn gameflow_session(&self) -> Result<GameflowSession> {
+            let queue_id = self.state().queue_id.context("no gameflow session")?;
+            Ok(serde_json::from_value(serde_json::json!({
+                "gameData": { "queue": { "id": queue_id } }
+            }))?)
+        }
+
+        async fn chat_participants(&self) -> Result<Lobby> {
+            Ok(Lobby {
+                participants: self.state().participants.clone(),
+            })
+        }
+
+        async fn champ_select_session(&self) -> Result<ChampSelectSession> {
+            self.state()
+                .session
+                .clone()
+                .context("no Champ Select session")
+        }
+
+        async fn region_locale(&self) -> Result<RegionInfo> {
+            Ok(RegionInfo {
+                web_region: self.state().web_region.clone(),
+            })
+        }
+
+        async fn current_summoner(&self) -> Result<Summoner> {
+            Ok(self.state().summoner.clone().unwrap_or_else(|| Summoner {
+                game_name: "Local Player".to_string(),
+                tag_line: "NA1".to_string(),
+                puuid: "local-puuid".to_string(),
+            }))
+        }
+
+        async fn ready_check(&self) -> Result<ReadyCheck> {
+            self.state().ready_check.clone().context("no ready check")
+        }
+
+        async fn accept_ready_check(&self) -> Result<()> {
+            self.state().actions.push("accept_ready_check");
+            Ok(())
+        }
+
+        async fn decline_ready_check(&self) -> Result<()> {
+            self.state().actions.push("decline_ready_check");
+            Ok(())
+        }
+
+        async fn quit_champ_select(&self) -> Result<()> {
+            self.state().actions.push("quit_champ_select");
+            Ok(())
+        }
+
+        async fn patch_champ_select_action(
+            &self,
+            action_id: u64,
+            champion_id: u32,
+            completed: bool,
+        ) -> Result<()> {
+            let mut state = self.state();
+            state.actions.push("patch_champ_select_action");
+            state
+                .action_patches
+                .push((action_id, champion_id, completed));
+            Ok(())
+        }
+
+        async fn set_summoner_spells(&self, spell1_id: u64, spell2_id: u64) -> Result<()> {
+            self.state().summoner_spells = Some((spell1_id, spell2_id));
+            Ok(())
+        }
+
+        async fn rune_pages(&self) -> Result<Vec<RunePageSummary>> {
+            Ok(self.state().rune_pages.clone())
+        }
+
+        async fn delete_rune_page(&self, page_id: u64) -> Result<()> {
+            let mut state = self.state();
+            state.actions.push("delete_rune_page");
+            state.rune_pages.retain(|page| page.id != page_id);
+            Ok(())
+        }
+
+        async fn create_rune_page(&self, page: &RunePage) -> Result<()> {
+            let mut state = self.state();
+            let id = state
+                .rune_pages
+                .iter()
+                .map(|page| page.id)
+                .max()
+                .unwrap_or(0)
+                + 1;
+            state.rune_pages.push(RunePageSummary {
+                id,
+                name: page.name.clone(),
+                is_deletable: true,
+            });
+            state.created_rune_pages.push(page.clone());
+            Ok(())
+        }
+
+        async fn end_of_game_stats(&self) -> Result<EndOfGameStats> {
+            self.state()
+                .end_of_game
+                .clone()
+                .context("no end-of-game stats")
+        }
+
+        async fn match_history(&self, puuid: &str) -> Result<MatchHistory> {
+            self.state()
+                .match_histories
+                .get(puuid)
+                .cloned()
+                .context("no match history")
+        }
+
+        async fn ranked_stats(&self, puuid: &str) -> Result<RankedStats> {
+            self.state()
+                .ranked_stats
+                .get(puuid)
+                .cloned()
+                .context("no ranked stats")
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    emitter::Emitter,
+    lobby::{Lobby, Participant},
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// The in-game Live Client Data API, served by the game process once the
+/// loading screen is over.
+pub const ALL_GAME_DATA_URL: &str = "https://127.0.0.1:2999/liveclientdata/allgamedata";
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The roster of the game in progress, kept for the enemy team multi-search.
+#[derive(Default)]
+pub struct InGame(pub Mutex<Option<InGameRoster>>);
+
+impl InGame {
+    /// Forgets the roster once the game is over.
+    pub async fn clear(&self) {
+        if self.0.lock().await.take().is_some() {
+            log_info!("Cleared the in-game roster");
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InGameRoster {
+    pub allies: Vec<RosterPlayer>,
+    pub enemies: Vec<RosterPlayer>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RosterPlayer {
+    pub game_name: String,
+    pub game_tag: String,
+    pub champion_name: String,
+    /// The client's position name, such as `JUNGLE`; empty in modes without one.
+    pub position: String,
+    pub summoner_spells: [String; 2],
+    pub is_bot: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AllGameData {
+    active_player: ActivePlayer,
+    all_players: Vec<LivePlayer>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivePlayer {
+    #[serde(default)]
+    riot_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LivePlayer {
+    champion_name: String,
+    #[serde(default)]
+    is_bot: bool,
+    #[serde(default)]
+    position: String,
+    #[serde(default)]
+    riot_id: String,
+    #[serde(default)]
+    riot_id_game_name: String,
+    #[serde(default)]
+    riot_id_tag_line: String,
+    summoner_spells: SummonerSpells,
+    team: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SummonerSpells {
+    summoner_spell_one: SummonerSpell,
+    summoner_spell_two: SummonerSpell,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SummonerSpell {
+    display_name: String,
+}
+
+impl TryFrom<AllGameData> for InGameRoster {
+    type Error = anyhow::Error;
+
+    fn try_from(data: AllGameData) -> Result<Self> {
+        let local_team = data
+            .all_players
+            .iter()
+            .find(|player| player.riot_id == data.active_player.riot_id)
+            .map(|player| player.team.clone())
+            .with_context(|| {
+                format!(
+                    "the active player {:?} is not in the player list",
+                    data.active_player.riot_id
+                )
+            })?;
+
+        let (allies, enemies) = data
+            .all_players
+            .into_iter()
+            .partition::<Vec<_>, _>(|player| player.team == local_team);
+
+        Ok(Self {
+            allies: allies.into_iter().map(RosterPlayer::from).collect(),
+            enemies: enemies.into_iter().map(RosterPlayer::from).collect(),
+        })
+    }
+}
+
+impl From<LivePlayer> for RosterPlayer {
+    fn from(player: LivePlayer) -> Self {
+        Self {
+            game_name: player.riot_id_game_name,
+            game_tag: player.riot_id_tag_line,
+            champion_name: player.champion_name,
+            position: player.position,
+            summoner_spells: [
+                player.summoner_spells.summoner_spell_one.display_name,
+                player.summoner_spells.summoner_spell_two.display_name,
+            ],
+            is_bot: player.is_bot,
+        }
+    }
+}
+
+impl InGameRoster {
+    /// The enemy players that can be looked up by Riot ID.
+    pub fn enemy_lobby(&self) -> Lobby {
+        Lobby {
+            participants: self
+                .enemies
+                .iter()
+                .filter(|player| !player.is_bot)
+                .map(|player| Participant::from_riot_id(&player.game_name, &player.game_tag))
+                .collect(),
+        }
+    }
+}
+
+/// Waits for the game to load, then stores and emits both teams. Runs until
+/// the roster is found; the phase supervisor cancels it when the game ends.
+pub async fn reveal_roster(in_game: &InGame, emitter: &impl Emitter) {
+    let client = match client() {
+        Ok(client) => client,
+        Err(error) => {
+            log_error!("Failed to create the Live Client Data API client: {error:#}");
+            return;
+        }
+    };
+
+    let roster = wait_for_roster(&client, ALL_GAME_DATA_URL, POLL_INTERVAL).await;
+    *in_game.0.lock().await = Some(roster.clone());
+    emit_roster(emitter, roster);
+}
+
+fn emit_roster(emitter: &impl Emitter, roster: InGameRoster) {
+    log_info!(
+        "In-game roster found: {} allies, {} enemies",
+        roster.allies.len(),
+        roster.enemies.len()
+    );
+    if let Err(error) = emitter.emit_event("in_game_roster", roster) {
+        log_error!("Failed to emit the in-game roster: {error}");
+    }
+}
+
+fn client() -> Result<reqwest::Client> {
+    // The game serves the API with a certificate signed by Riot's own root.
+    reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .context("failed to build HTTP client")
+}
+
+async fn wait_for_roster(
+    client: &reqwest::Client,
+    url: &str,
+    poll_interval: Duration,
+) -> InGameRoster {
+    let mut interval = tokio::time::interval(poll_interval);
+    let mut attempts = 0;
+
+    loop {
+        interval.tick().await;
+        match fetch_roster(client, url).await {
+            Ok(roster) => return roster,
+            // The API is unavailable until the loading screen is over
+            Err(error) if attempts == 0 => {
+                log_info!("Waiting for the Live Client Data API: {error:#}");
+            }
+            Err(_) => {}
+        }
+        attempts += 1;
+    }
+}
+
+async fn fetch_roster(client: &reqwest::Client, url: &str) -> Result<InGameRoster> {
+    let body = client
+        .get(url)
+        .send()
+        .await
+        .context("request failed")?
+        .error_for_status()?
+        .bytes()
+        .await
+        .context("failed to read response")?;
+    let data: AllGameData =
+        serde_json::from_slice(&body).context("failed to parse live game data")?;
+
+    data.try_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{emitter::RecordingEmitter, mock_lcu::MockLcu};
+
+    const ALL_GAME_DATA_URI: &str = "/liveclientdata/allgamedata";
+
+    fn all_game_data() -> serde_json::Value {
+        serde_json::from_str(include_str!("../fixtures/live-client/allgamedata.json")).unwrap()
+    }
+
+    #[test]
+    fn splits_players_by_the_local_players_team() {
+        let data: AllGameData = serde_json::from_value(all_game_data()).unwrap();
+        let roster = InGameRoster::try_from(data).unwrap();
+
+        let allies: Vec<_> = roster.allies.iter().map(|p| p.game_name.as_str()).collect();
+        assert_eq!(allies, ["Player One", "Second Player"]);
+        assert_eq!(
+            roster.enemies[0],
+            RosterPlayer {
+                game_name: "Enemy Top".to_string(),
+                game_tag: "EUW".to_string(),
+                champion_name: "Garen".to_string(),
+                position: "TOP".to_string(),
+                summoner_spells: ["Flash".to_string(), "Teleport".to_string()],
+                is_bot: false,
+            }
+        );
+
+        let enemies = roster.enemy_lobby();
+        assert_eq!(enemies.participants.len(), 1);
+        assert_eq!(enemies.participants[0].game_tag, "EUW");
+    }
+
+    #[test]
+    fn rejects_data_without_the_active_player() {
+        let mut json = all_game_data();
+        json["activePlayer"]["riotId"] = "Spectator#NA1".into();
+        let data: AllGameData = serde_json::from_value(json).unwrap();
+
+        let error = InGameRoster::try_from(data).unwrap_err();
+
+        assert!(error.to_string().contains("Spectator#NA1"));
+    }
+
+    #[tokio::test]
+    async fn polls_the_stub_server_until_the_game_has_loaded() {
+        let mock = MockLcu::start().await.unwrap();
+        let url = format!("https://127.0.0.1:{}{ALL_GAME_DATA_URI}", mock.port());
+        let client = mock.client();
+
+        let roster =
+            tokio::spawn(
+                async move { wait_for_roster(&client, &url, Duration::from_millis(10)).await },
+            );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!roster.is_finished());
+
+        mock.set(ALL_GAME_DATA_URI, all_game_data());
+        let roster = tokio::time::timeout(Duration::from_secs(5), roster)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let emitter = RecordingEmitter::default();
+        emit_roster(&emitter, roster);
+        let payload = &emitter.payloads("in_game_roster")[0];
+        assert_eq!(payload["allies"][0]["championName"], "Lee Sin");
+        assert_eq!(payload["enemies"][1]["isBot"], true);
+    }
+}
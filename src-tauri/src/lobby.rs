@@ -1,5 +1,7 @@
+use crate::{lcu_api::LcuApi, player_notes::PlayerNote};
 use serde::{Deserialize, Serialize};
-use shaco::rest::RESTClient;
+
+pub const CHAT_PARTICIPANTS_URI: &str = "/chat/v5/participants";
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Participant {
@@ -17,6 +19,45 @@ pub struct Participant {
     pub cell_id: Option<u32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pick_turn: Option<u32>,
+    /// Earlier games with this player, from the lobby history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub played_with: Option<PlayedWith>,
+    /// The user's own note about this player.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<PlayerNote>,
+}
+
+/// How often the local player was matched with someone before.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PlayedWith {
+    pub games: usize,
+    pub wins: usize,
+    pub losses: usize,
+    /// When the most recent of those lobbies was revealed, as RFC 3339.
+    pub last_played_at: String,
+    /// `None` when the result of that game is unknown.
+    pub last_won: Option<bool>,
+}
+
+impl Participant {
+    /// A player known only by Riot ID, such as an enemy seen in game.
+    pub fn from_riot_id(game_name: &str, game_tag: &str) -> Self {
+        Self {
+            cid: String::new(),
+            game_name: game_name.to_string(),
+            game_tag: game_tag.to_string(),
+            muted: false,
+            name: game_name.to_string(),
+            pid: String::new(),
+            puuid: String::new(),
+            region: String::new(),
+            assigned_position: None,
+            cell_id: None,
+            pick_turn: None,
+            played_with: None,
+            note: None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -24,10 +65,14 @@ pub struct Lobby {
     pub participants: Vec<Participant>,
 }
 
-pub async fn get_lobby_info(app_client: &RESTClient) -> anyhow::Result<Lobby> {
-    let team: Lobby =
-        serde_json::from_value(app_client.get("/chat/v5/participants".to_string()).await?)?;
+pub async fn get_lobby_info(lcu: &impl LcuApi) -> anyhow::Result<Lobby> {
+    let team = lcu.chat_participants().await?;
 
+    Ok(champ_select_participants(team))
+}
+
+/// Keeps only the participants of the Champ Select chat room.
+pub fn champ_select_participants(team: Lobby) -> Lobby {
     // filter out all cids that contain champ-select
     let team_participants = team
         .participants
@@ -35,9 +80,7 @@ pub async fn get_lobby_info(app_client: &RESTClient) -> anyhow::Result<Lobby> {
         .filter(|p| p.cid.contains("champ-select"))
         .collect::<Vec<Participant>>();
 
-    let team = Lobby {
+    Lobby {
         participants: team_participants,
-    };
-
-    Ok(team)
+    }
 }
@@ -0,0 +1,656 @@
+use crate::{
+    lobby::{Lobby, Participant, PlayedWith},
+    store::{self, Dir},
+};
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{BufRead, BufReader},
+    path::PathBuf,
+    sync::Arc,
+};
+use tauri::AppHandle;
+use tokio::{io::AsyncWriteExt, sync::Mutex};
+
+const HISTORY_FILE: &str = "lobby_history.jsonl";
+
+/// A lobby revealed during Champ Select.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub game_id: u64,
+    /// `-1` when the queue could not be read.
+    pub queue_id: i64,
+    pub region: String,
+    /// RFC 3339 timestamps.
+    pub revealed_at: String,
+    pub updated_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_started_at: Option<String>,
+    /// Set once the game's end-of-game stats are known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<GameResult>,
+    pub participants: Vec<Participant>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameResult {
+    pub won: bool,
+    pub champion_id: u32,
+    pub champion_name: String,
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+    pub game_length_seconds: u64,
+}
+
+impl HistoryEntry {
+    fn includes(&self, puuid: &str) -> bool {
+        self.participants
+            .iter()
+            .any(|participant| participant.puuid == puuid)
+    }
+
+    fn played_with(&self, riot_id: &str) -> bool {
+        self.participants.iter().any(|participant| {
+            format!("{}#{}", participant.game_name, participant.game_tag)
+                .to_lowercase()
+                .contains(riot_id)
+        })
+    }
+}
+
+/// An entry of the history the frontend kept in localStorage before the
+/// backend owned it. Those entries have no game ID or queue.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyEntry {
+    pub id: String,
+    pub revealed_at: String,
+    #[serde(default)]
+    pub game_started_at: Option<String>,
+    pub participants: Vec<Participant>,
+}
+
+/// Imported entries get game IDs from here up, above any real game ID but
+/// still exact as a JavaScript number.
+const LEGACY_GAME_ID_BASE: u64 = 1 << 52;
+
+/// Lobby history kept in an append-only JSON-lines file under the app data
+/// directory. A later line for the same game replaces earlier ones; the file
+/// is compacted when opened and rewritten when entries are deleted.
+#[derive(Clone)]
+pub struct LobbyHistory(Arc<Mutex<HistoryStore>>);
+
+struct HistoryStore {
+    /// `None` keeps the history in memory, when the file could not be loaded.
+    path: Option<PathBuf>,
+    /// Oldest first.
+    entries: Vec<HistoryEntry>,
+}
+
+impl LobbyHistory {
+    pub fn load(app_handle: &AppHandle) -> Result<Self> {
+        Self::open(store::path(app_handle, Dir::Data, HISTORY_FILE)?)
+    }
+
+    /// A history that is not saved, for when [`load`](Self::load) fails.
+    pub fn in_memory() -> Self {
+        Self(Arc::new(Mutex::new(HistoryStore {
+            path: None,
+            entries: Vec::new(),
+        })))
+    }
+
+    fn open(path: PathBuf) -> Result<Self> {
+        let mut store = HistoryStore {
+            path: Some(path.clone()),
+            entries: Vec::new(),
+        };
+        if !path.exists() {
+            return Ok(Self(Arc::new(Mutex::new(store))));
+        }
+
+        let file = std::fs::File::open(&path).context("failed to open lobby history")?;
+        let mut lines = 0;
+        for line in BufReader::new(file).lines() {
+            let line = line.context("failed to read lobby history")?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            lines += 1;
+            match serde_json::from_str(&line) {
+                Ok(entry) => store.upsert(entry),
+                Err(error) => log_warn!("Skipping unreadable lobby history line {lines}: {error}"),
+            }
+        }
+
+        if lines > store.entries.len() {
+            store::write_sync(&path, store.to_json_lines()?)
+                .context("failed to compact lobby history")?;
+        }
+        log_info!("Loaded {} lobby history entries", store.entries.len());
+        Ok(Self(Arc::new(Mutex::new(store))))
+    }
+
+    /// Every entry, most recently revealed first.
+    pub async fn list(&self) -> Vec<HistoryEntry> {
+        let store = self.0.lock().await;
+        store.entries.iter().rev().cloned().collect()
+    }
+
+    /// Entries with a participant whose `name#tag` contains `riot_id`,
+    /// ignoring case.
+    pub async fn search(&self, riot_id: &str) -> Vec<HistoryEntry> {
+        let riot_id = riot_id.trim().to_lowercase();
+        let store = self.0.lock().await;
+        store
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.played_with(&riot_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Annotates everyone but the local player with the earlier games they
+    /// were in. `current_game_id` is left out so a game never counts itself.
+    pub async fn annotate_played_with(
+        &self,
+        lobby: &mut Lobby,
+        current_game_id: Option<u64>,
+        local_puuid: &str,
+    ) {
+        let store = self.0.lock().await;
+        for participant in &mut lobby.participants {
+            participant.played_with =
+                if participant.puuid.is_empty() || participant.puuid == local_puuid {
+                    None
+                } else {
+                    store.played_with(&participant.puuid, current_game_id)
+                };
+        }
+    }
+
+    /// Records the lobby of `game_id`, keeping participants seen earlier in
+    /// the same game.
+    pub async fn record(
+        &self,
+        game_id: u64,
+        queue_id: i64,
+        region: &str,
+        lobby: &Lobby,
+    ) -> Result<()> {
+        // Annotations are derived from the history, so they are not stored.
+        let participants: Vec<_> = lobby
+            .participants
+            .iter()
+            .map(|participant| Participant {
+                played_with: None,
+                note: None,
+                ..participant.clone()
+            })
+            .collect();
+        let now = timestamp();
+        let mut store = self.0.lock().await;
+        let entry = match store.entries.iter().find(|entry| entry.game_id == game_id) {
+            Some(existing) => HistoryEntry {
+                updated_at: now,
+                participants: merge_participants(&existing.participants, &participants),
+                ..existing.clone()
+            },
+            None => HistoryEntry {
+                game_id,
+                queue_id,
+                region: region.to_string(),
+                revealed_at: now.clone(),
+                updated_at: now,
+                game_started_at: None,
+                result: None,
+                participants,
+            },
+        };
+
+        store.append(entry).await
+    }
+
+    /// Notes that the game of `game_id` started, if it was recorded.
+    pub async fn mark_game_started(&self, game_id: u64) -> Result<()> {
+        let mut store = self.0.lock().await;
+        let Some(existing) = store.entries.iter().find(|entry| entry.game_id == game_id) else {
+            return Ok(());
+        };
+        if existing.game_started_at.is_some() {
+            return Ok(());
+        }
+
+        let now = timestamp();
+        let entry = HistoryEntry {
+            updated_at: now.clone(),
+            game_started_at: Some(now),
+            ..existing.clone()
+        };
+        store.append(entry).await
+    }
+
+    /// Attaches the result of `game_id`, returning whether it was recorded.
+    pub async fn set_result(&self, game_id: u64, result: GameResult) -> Result<bool> {
+        let mut store = self.0.lock().await;
+        let Some(existing) = store.entries.iter().find(|entry| entry.game_id == game_id) else {
+            return Ok(false);
+        };
+
+        let entry = HistoryEntry {
+            updated_at: timestamp(),
+            result: Some(result),
+            ..existing.clone()
+        };
+        store.append(entry).await?;
+        Ok(true)
+    }
+
+    /// Removes the entry for `game_id`, returning whether there was one.
+    pub async fn delete(&self, game_id: u64) -> Result<bool> {
+        let mut store = self.0.lock().await;
+        let count = store.entries.len();
+        store.entries.retain(|entry| entry.game_id != game_id);
+        if store.entries.len() == count {
+            return Ok(false);
+        }
+
+        store.rewrite().await?;
+        Ok(true)
+    }
+
+    pub async fn clear(&self) -> Result<()> {
+        let mut store = self.0.lock().await;
+        store.entries.clear();
+        store.rewrite().await
+    }
+
+    /// Adds the entries the frontend kept in localStorage, older than any
+    /// entry recorded here. Returns how many were new.
+    pub async fn import(&self, legacy: Vec<LegacyEntry>) -> Result<usize> {
+        let mut store = self.0.lock().await;
+        let mut imported = Vec::new();
+        // localStorage kept the most recent lobby first
+        for entry in legacy.into_iter().rev() {
+            let game_id = legacy_game_id(&entry.id);
+            if store
+                .entries
+                .iter()
+                .any(|existing| existing.game_id == game_id)
+                || imported
+                    .iter()
+                    .any(|existing: &HistoryEntry| existing.game_id == game_id)
+            {
+                continue;
+            }
+            imported.push(HistoryEntry {
+                game_id,
+                queue_id: -1,
+                region: entry
+                    .participants
+                    .first()
+                    .map(|participant| participant.region.clone())
+                    .unwrap_or_default(),
+                updated_at: entry.revealed_at.clone(),
+                revealed_at: entry.revealed_at,
+                game_started_at: entry.game_started_at,
+                result: None,
+                participants: entry.participants,
+            });
+        }
+
+        let count = imported.len();
+        if count > 0 {
+            store.entries.splice(0..0, imported);
+            store.rewrite().await?;
+        }
+        log_info!("Imported {count} lobby history entries from the frontend");
+        Ok(count)
+    }
+
+    /// Every entry as a pretty-printed JSON array, most recent first.
+    pub async fn export(&self) -> Result<String> {
+        serde_json::to_string_pretty(&self.list().await)
+            .context("failed to serialize lobby history")
+    }
+}
+
+impl HistoryStore {
+    fn played_with(&self, puuid: &str, current_game_id: Option<u64>) -> Option<PlayedWith> {
+        let games: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|entry| Some(entry.game_id) != current_game_id && entry.includes(puuid))
+            .collect();
+        let last_game = games.last()?;
+        let count_results = |won: bool| {
+            games
+                .iter()
+                .filter(|entry| {
+                    entry
+                        .result
+                        .as_ref()
+                        .is_some_and(|result| result.won == won)
+                })
+                .count()
+        };
+
+        Some(PlayedWith {
+            games: games.len(),
+            wins: count_results(true),
+            losses: count_results(false),
+            last_played_at: last_game.revealed_at.clone(),
+            last_won: last_game.result.as_ref().map(|result| result.won),
+        })
+    }
+
+    fn upsert(&mut self, entry: HistoryEntry) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|existing| existing.game_id == entry.game_id)
+        {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    async fn append(&mut self, entry: HistoryEntry) -> Result<()> {
+        let Some(path) = &self.path else {
+            self.upsert(entry);
+            return Ok(());
+        };
+        let mut line = serde_json::to_string(&entry).context("failed to serialize entry")?;
+        line.push('\n');
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .context("failed to open lobby history")?;
+        file.write_all(line.as_bytes())
+            .await
+            .context("failed to write lobby history")?;
+        // tokio finishes file writes in the background until flushed
+        file.flush()
+            .await
+            .context("failed to write lobby history")?;
+
+        self.upsert(entry);
+        Ok(())
+    }
+
+    /// Replaces the file with the current entries.
+    async fn rewrite(&self) -> Result<()> {
+        match &self.path {
+            Some(path) => store::write(path, self.to_json_lines()?).await,
+            None => Ok(()),
+        }
+    }
+
+    fn to_json_lines(&self) -> Result<String> {
+        let mut json_lines = String::new();
+        for entry in &self.entries {
+            json_lines
+                .push_str(&serde_json::to_string(entry).context("failed to serialize entry")?);
+            json_lines.push('\n');
+        }
+        Ok(json_lines)
+    }
+}
+
+/// A stable game ID for a localStorage entry, so importing twice adds it once.
+fn legacy_game_id(id: &str) -> u64 {
+    // FNV-1a, which unlike `DefaultHasher` is the same in every Rust release
+    let hash = id.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    LEGACY_GAME_ID_BASE | (hash % LEGACY_GAME_ID_BASE)
+}
+
+fn timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn participant_key(participant: &Participant) -> String {
+    if participant.puuid.is_empty() {
+        format!("{}#{}", participant.game_name, participant.game_tag).to_lowercase()
+    } else {
+        participant.puuid.clone()
+    }
+}
+
+/// The latest participants, followed by earlier ones who are no longer listed.
+fn merge_participants(earlier: &[Participant], latest: &[Participant]) -> Vec<Participant> {
+    let mut participants = latest.to_vec();
+    for participant in earlier {
+        let key = participant_key(participant);
+        if !latest.iter().any(|latest| participant_key(latest) == key) {
+            participants.push(participant.clone());
+        }
+    }
+    participants
+}
+
+#[cfg(test)]
+impl LobbyHistory {
+    /// A history backed by a fresh file in the system temp directory.
+    pub fn temporary() -> Self {
+        Self::open(store::temporary_dir("lobby-history").join(HISTORY_FILE)).unwrap()
+    }
+
+    async fn path(&self) -> PathBuf {
+        self.0.lock().await.path.clone().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lobby(players: &[(&str, &str)]) -> Lobby {
+        Lobby {
+            participants: players
+                .iter()
+                .map(|(name, tag)| Participant {
+                    puuid: format!("puuid-{name}"),
+                    ..Participant::from_riot_id(name, tag)
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_every_game_across_reopening_the_store() {
+        let history = LobbyHistory::temporary();
+        for game_id in 0..60 {
+            history
+                .record(game_id, 420, "NA", &lobby(&[("Player", "NA1")]))
+                .await
+                .unwrap();
+        }
+        history
+            .record(59, 420, "NA", &lobby(&[("Second", "EUW")]))
+            .await
+            .unwrap();
+        history.mark_game_started(59).await.unwrap();
+
+        let reopened = LobbyHistory::open(history.path().await).unwrap();
+        let entries = reopened.list().await;
+
+        assert_eq!(entries.len(), 60);
+        assert_eq!(entries[0].game_id, 59);
+        assert_eq!(
+            entries[0].game_started_at.as_ref(),
+            Some(&entries[0].updated_at)
+        );
+        let names: Vec<_> = entries[0]
+            .participants
+            .iter()
+            .map(|p| p.game_name.as_str())
+            .collect();
+        assert_eq!(names, ["Second", "Player"]);
+
+        let compacted = std::fs::read_to_string(reopened.path().await).unwrap();
+        assert_eq!(compacted.lines().count(), 60);
+    }
+
+    #[tokio::test]
+    async fn annotates_players_from_earlier_games() {
+        let path = LobbyHistory::temporary().path().await;
+        let entry = |game_id: u64, won: Option<bool>, names: &[(&str, &str)]| {
+            let mut entry = serde_json::json!({
+                "gameId": game_id,
+                "queueId": 420,
+                "region": "NA",
+                "revealedAt": format!("2026-01-0{game_id}T20:00:00.000Z"),
+                "updatedAt": format!("2026-01-0{game_id}T20:00:00.000Z"),
+                "participants": lobby(names).participants
+            });
+            if let Some(won) = won {
+                entry["result"] = serde_json::json!({
+                    "won": won,
+                    "championId": 64,
+                    "championName": "Lee Sin",
+                    "kills": 1,
+                    "deaths": 2,
+                    "assists": 3,
+                    "gameLengthSeconds": 1800
+                });
+            }
+            entry.to_string()
+        };
+        let lines = [
+            entry(1, Some(true), &[("Me", "NA1"), ("Duo", "NA1")]),
+            entry(2, Some(false), &[("Me", "NA1"), ("Duo", "NA1")]),
+            entry(
+                3,
+                None,
+                &[("Me", "NA1"), ("Duo", "NA1"), ("Griefer", "NA1")],
+            ),
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let history = LobbyHistory::open(path).unwrap();
+
+        let mut team = lobby(&[
+            ("Me", "NA1"),
+            ("Duo", "NA1"),
+            ("Griefer", "NA1"),
+            ("New", "NA1"),
+        ]);
+        history
+            .annotate_played_with(&mut team, Some(3), "puuid-Me")
+            .await;
+
+        assert_eq!(team.participants[0].played_with, None);
+        assert_eq!(
+            team.participants[1].played_with,
+            Some(PlayedWith {
+                games: 2,
+                wins: 1,
+                losses: 1,
+                last_played_at: "2026-01-02T20:00:00.000Z".to_string(),
+                last_won: Some(false),
+            })
+        );
+        assert_eq!(team.participants[2].played_with, None);
+        assert_eq!(team.participants[3].played_with, None);
+
+        history
+            .annotate_played_with(&mut team, None, "puuid-Me")
+            .await;
+        let griefer = team.participants[2].played_with.as_ref().unwrap();
+        assert_eq!((griefer.games, griefer.last_won), (1, None));
+
+        history.record(4, 420, "NA", &team).await.unwrap();
+        assert!(history.list().await[0]
+            .participants
+            .iter()
+            .all(|participant| participant.played_with.is_none()));
+    }
+
+    #[tokio::test]
+    async fn searches_deletes_and_exports_entries() {
+        let history = LobbyHistory::temporary();
+        history
+            .record(1, 420, "NA", &lobby(&[("Paper Lantern", "NA1")]))
+            .await
+            .unwrap();
+        history
+            .record(2, 440, "EUW", &lobby(&[("River Walker", "SUP")]))
+            .await
+            .unwrap();
+
+        let found = history.search(" paper lantern#na ").await;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].game_id, 1);
+        assert!(history.search("nobody").await.is_empty());
+
+        assert!(history.delete(1).await.unwrap());
+        assert!(!history.delete(1).await.unwrap());
+        let reopened = LobbyHistory::open(history.path().await).unwrap();
+        assert_eq!(reopened.list().await.len(), 1);
+
+        let exported: Vec<HistoryEntry> =
+            serde_json::from_str(&reopened.export().await.unwrap()).unwrap();
+        assert_eq!(exported[0].region, "EUW");
+        assert_eq!(exported[0].queue_id, 440);
+
+        reopened.clear().await.unwrap();
+        assert!(LobbyHistory::open(history.path().await)
+            .unwrap()
+            .list()
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn imports_the_localstorage_history_once() {
+        let history = LobbyHistory::temporary();
+        history
+            .record(7, 420, "NA", &lobby(&[("Current", "NA1")]))
+            .await
+            .unwrap();
+        let participants = |name: &str| {
+            let mut lobby = lobby(&[(name, "EUW")]);
+            lobby.participants[0].region = "EUW".to_string();
+            lobby.participants
+        };
+        let legacy: Vec<LegacyEntry> = serde_json::from_value(serde_json::json!([
+            {
+                "id": "9b1d6c52-newer",
+                "revealedAt": "2026-01-02T20:00:00.000Z",
+                "gameStartedAt": "2026-01-02T20:05:00.000Z",
+                "participants": participants("Newer")
+            },
+            {
+                "id": "3f0a41e8-older",
+                "revealedAt": "2026-01-01T20:00:00.000Z",
+                "participants": participants("Older")
+            }
+        ]))
+        .unwrap();
+
+        assert_eq!(history.import(legacy.clone()).await.unwrap(), 2);
+        assert_eq!(history.import(legacy).await.unwrap(), 0);
+
+        let entries = LobbyHistory::open(history.path().await)
+            .unwrap()
+            .list()
+            .await;
+        let names: Vec<_> = entries
+            .iter()
+            .map(|entry| entry.participants[0].game_name.as_str())
+            .collect();
+        assert_eq!(names, ["Current", "Newer", "Older"]);
+        assert_eq!(entries[1].region, "EUW");
+        assert_eq!(entries[1].queue_id, -1);
+        assert!(entries[1].game_id >= LEGACY_GAME_ID_BASE);
+        assert!(entries[1].game_id < 1 << 53);
+    }
+}
@@ -1,11 +1,16 @@
 use anyhow::{Context, Result};
 use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
 use std::{
     fmt,
     fs::{self, File, OpenOptions},
+    future::Future,
     io::Write,
     path::PathBuf,
-    sync::{Mutex, OnceLock},
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        Mutex, OnceLock,
+    },
 };
 
 const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
@@ -13,8 +18,98 @@ const LOG_DIRECTORY_NAME: &str = "logs";
 const LOG_FILE_NAME: &str = "reveal.log";
 const PREVIOUS_LOG_FILE_NAME: &str = "reveal.previous.log";
 
+/// `debug` or `trace` to start with a more verbose level than `info`.
+const LOG_LEVEL_OVERRIDE: &str = "REVEAL_LOG_LEVEL";
+/// `json` to write one JSON object per line instead of plain text.
+const LOG_FORMAT_OVERRIDE: &str = "REVEAL_LOG_FORMAT";
+
 static LOG_FILE: OnceLock<Mutex<File>> = OnceLock::new();
 static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+static JSON_LINES: AtomicBool = AtomicBool::new(false);
+
+tokio::task_local! {
+    /// Ties together the lines of one flow, e.g. `game-123` for a Champ
+    /// Select or `lcu-2` for the second client connection.
+    static CORRELATION: String;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    const ALL: [Level; 5] = [
+        Level::Error,
+        Level::Warn,
+        Level::Info,
+        Level::Debug,
+        Level::Trace,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|level| level.as_str().eq_ignore_ascii_case(value.trim()))
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Lines less severe than `level` are dropped from now on.
+pub fn set_level(level: Level) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+pub fn level() -> Level {
+    Level::ALL[usize::from(MAX_LEVEL.load(Ordering::Relaxed))]
+}
+
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Runs `future` with `id` as the correlation of every line it logs,
+/// including lines from tasks it starts through the phase supervisor.
+pub async fn scoped<F: Future>(id: impl Into<String>, future: F) -> F::Output {
+    CORRELATION.scope(id.into(), future).await
+}
+
+/// Carries the current correlation over to `future`, which is about to be
+/// spawned as a task of its own.
+pub fn inherit<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let correlation = correlation();
+    async move {
+        match correlation {
+            Some(id) => CORRELATION.scope(id, future).await,
+            None => future.await,
+        }
+    }
+}
+
+pub fn correlation() -> Option<String> {
+    CORRELATION.try_with(Clone::clone).ok()
+}
 
 pub fn init() -> Result<PathBuf> {
     if let Some(path) = LOG_PATH.get() {
@@ -45,6 +140,17 @@ pub fn init() -> Result<PathBuf> {
     let _ = LOG_PATH.set(log_path.clone());
     install_panic_hook();
 
+    if let Some(level) = std::env::var(LOG_LEVEL_OVERRIDE)
+        .ok()
+        .and_then(|value| Level::parse(&value))
+    {
+        set_level(level);
+    }
+    JSON_LINES.store(
+        std::env::var(LOG_FORMAT_OVERRIDE).is_ok_and(|format| format.eq_ignore_ascii_case("json")),
+        Ordering::Relaxed,
+    );
+
     Ok(log_path)
 }
 
@@ -83,16 +189,30 @@ fn install_panic_hook() {
     let _ = std::panic::take_hook();
 
     std::panic::set_hook(Box::new(move |panic_info| {
-        write("ERROR", format_args!("Unhandled panic: {panic_info}"));
+        write(
+            Level::Error,
+            module_path!(),
+            format_args!("Unhandled panic: {panic_info}"),
+        );
 
         #[cfg(debug_assertions)]
         previous_hook(panic_info);
     }));
 }
 
-pub fn write(level: &str, message: fmt::Arguments<'_>) {
-    let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
-    let line = format!("{timestamp} [{level}] {message}");
+pub fn write(level: Level, target: &str, message: fmt::Arguments<'_>) {
+    if !enabled(level) {
+        return;
+    }
+
+    let record = Record {
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        level,
+        target,
+        correlation: correlation(),
+        message: message.to_string(),
+    };
+    let line = record.format(JSON_LINES.load(Ordering::Relaxed));
 
     if let Some(file) = LOG_FILE.get() {
         let mut file = file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
@@ -104,6 +224,36 @@ pub fn write(level: &str, message: fmt::Arguments<'_>) {
     eprintln!("{line}");
 }
 
+/// One log line.
+#[derive(Serialize)]
+struct Record<'a> {
+    timestamp: String,
+    level: Level,
+    /// The module that logged it.
+    target: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    correlation: Option<String>,
+    message: String,
+}
+
+impl Record<'_> {
+    fn format(&self, json: bool) -> String {
+        if json {
+            if let Ok(line) = serde_json::to_string(self) {
+                return line;
+            }
+        }
+
+        match &self.correlation {
+            Some(id) => format!(
+                "{} [{}] [{id}] {}",
+                self.timestamp, self.level, self.message
+            ),
+            None => format!("{} [{}] {}", self.timestamp, self.level, self.message),
+        }
+    }
+}
+
 pub fn sanitize_frontend_message(message: &str) -> String {
     message
         .replace(['\r', '\n'], " ")
@@ -113,22 +263,113 @@ pub fn sanitize_frontend_message(message: &str) -> String {
 }
 
 #[macro_export]
-macro_rules! log_info {
+macro_rules! log_error {
     ($($arg:tt)*) => {
-        $crate::logging::write("INFO", format_args!($($arg)*))
+        $crate::logging::write(
+            $crate::logging::Level::Error,
+            module_path!(),
+            format_args!($($arg)*),
+        )
     };
 }
 
 #[macro_export]
 macro_rules! log_warn {
     ($($arg:tt)*) => {
-        $crate::logging::write("WARN", format_args!($($arg)*))
+        $crate::logging::write(
+            $crate::logging::Level::Warn,
+            module_path!(),
+            format_args!($($arg)*),
+        )
     };
 }
 
 #[macro_export]
-macro_rules! log_error {
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        $crate::logging::write(
+            $crate::logging::Level::Info,
+            module_path!(),
+            format_args!($($arg)*),
+        )
+    };
+}
+
+#[macro_export]
+macro_rules! log_debug {
     ($($arg:tt)*) => {
-        $crate::logging::write("ERROR", format_args!($($arg)*))
+        $crate::logging::write(
+            $crate::logging::Level::Debug,
+            module_path!(),
+            format_args!($($arg)*),
+        )
     };
 }
+
+#[macro_export]
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        $crate::logging::write(
+            $crate::logging::Level::Trace,
+            module_path!(),
+            format_args!($($arg)*),
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(correlation: Option<&str>) -> Record<'static> {
+        Record {
+            timestamp: "2024-05-01T18:30:00.000Z".to_string(),
+            level: Level::Debug,
+            target: "reveal::ready_check",
+            correlation: correlation.map(str::to_string),
+            message: "Accepting \"ready\" check".to_string(),
+        }
+    }
+
+    #[test]
+    fn formats_text_and_json_lines() {
+        assert_eq!(
+            record(None).format(false),
+            r#"2024-05-01T18:30:00.000Z [DEBUG] Accepting "ready" check"#
+        );
+        assert_eq!(
+            record(Some("game-42")).format(false),
+            r#"2024-05-01T18:30:00.000Z [DEBUG] [game-42] Accepting "ready" check"#
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&record(Some("game-42")).format(true))
+                .unwrap(),
+            serde_json::json!({
+                "timestamp": "2024-05-01T18:30:00.000Z",
+                "level": "debug",
+                "target": "reveal::ready_check",
+                "correlation": "game-42",
+                "message": "Accepting \"ready\" check",
+            })
+        );
+        assert_eq!(Level::parse(" trace"), Some(Level::Trace));
+        assert_eq!(Level::parse("verbose"), None);
+    }
+
+    #[tokio::test]
+    async fn spawned_tasks_keep_the_correlation() {
+        assert_eq!(correlation(), None);
+
+        let (inherited, plain) = scoped("lcu-1", async {
+            scoped("game-42", async {}).await;
+            let inherited = tokio::spawn(inherit(async { correlation() }));
+            let plain = tokio::spawn(async { correlation() });
+            (inherited.await.unwrap(), plain.await.unwrap())
+        })
+        .await;
+
+        assert_eq!(inherited.as_deref(), Some("lcu-1"));
+        assert_eq!(plain, None);
+        assert_eq!(correlation(), None);
+    }
+}
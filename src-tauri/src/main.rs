@@ -6,22 +6,54 @@ mod logging;
 
 mod analytics;
 mod app_state;
+mod auto_draft;
 mod champ_select;
 mod commands;
 mod config;
+mod config_validation;
+mod dodge;
+mod emitter;
+mod end_of_game;
+mod event_bus;
+mod gameflow;
 mod lcu;
+mod lcu_api;
+mod live_client;
 mod lobby;
+mod lobby_history;
+mod match_history;
+#[cfg(test)]
+mod mock_lcu;
+mod multi_search;
+mod player_notes;
+mod presets;
+mod profiles;
+mod ready_check;
 mod region;
+mod role_check;
 mod state;
+mod store;
 mod summoner;
+mod supervisor;
+mod teammate_stats;
 mod utils;
+mod websocket;
 
 use app_state::{Dodge, Lcu};
 use commands::{
-    app_ready, dodge, enable_dodge, get_config, get_lcu_info, get_lcu_state, open_opgg_link,
-    set_config, write_frontend_log,
+    app_ready, clear_lobby_history, create_multi_search_link, delete_lobby_history_entry,
+    delete_player_note, dodge, enable_dodge, export_lobby_history, export_presets,
+    get_active_profile, get_config, get_lcu_info, get_lcu_state, get_lobby_history,
+    get_multi_search_providers, get_player_notes, get_presets, get_running_tasks,
+    get_teammate_stats, import_lobby_history, import_presets, open_enemy_team_link, open_opgg_link,
+    save_player_note, search_lobby_history, set_config, set_log_level, write_frontend_log,
 };
-use config::AppConfig;
+use config::{ActiveAccount, AppConfig};
+use live_client::InGame;
+use lobby_history::LobbyHistory;
+use player_notes::PlayerNotes;
+use presets::AppPresets;
+use supervisor::PhaseSupervisor;
 use tauri::Manager;
 
 fn main() {
@@ -33,6 +65,9 @@ fn main() {
     let result = tauri::Builder::default()
         .manage(Lcu::default())
         .manage(Dodge::default())
+        .manage(PhaseSupervisor::default())
+        .manage(InGame::default())
+        .manage(ActiveAccount::default())
         .setup(|app| {
             let app_handle = app.handle();
             log_info!(
@@ -46,6 +81,28 @@ fn main() {
             })?;
             app.manage(AppConfig::new(loaded_config));
 
+            // Presets are optional; a broken file should not stop Reveal from starting.
+            let loaded_presets = presets::load(&app_handle).unwrap_or_else(|error| {
+                log_error!("Startup presets load failed: {error:#}");
+                Vec::new()
+            });
+            app.manage(AppPresets::new(loaded_presets));
+
+            // Reveal works without history, so only this session's lobbies are lost.
+            let history = LobbyHistory::load(&app_handle).unwrap_or_else(|error| {
+                log_error!("Startup lobby history load failed, keeping it in memory: {error:#}");
+                LobbyHistory::in_memory()
+            });
+            app.manage(history);
+
+            let notes = PlayerNotes::load(&app_handle).unwrap_or_else(|error| {
+                log_error!("Startup player notes load failed, keeping them in memory: {error:#}");
+                PlayerNotes::in_memory()
+            });
+            app.manage(notes);
+
+            tauri::async_runtime::spawn(config::watch(app_handle.clone()));
+
             tauri::async_runtime::spawn(async move {
                 log_info!("League Client monitor started");
                 lcu::run(app_handle).await;
@@ -59,10 +116,29 @@ fn main() {
             get_lcu_state,
             get_lcu_info,
             get_config,
+            get_active_profile,
             set_config,
             open_opgg_link,
+            open_enemy_team_link,
+            get_multi_search_providers,
+            create_multi_search_link,
             dodge,
             enable_dodge,
+            get_running_tasks,
+            get_presets,
+            import_presets,
+            export_presets,
+            get_lobby_history,
+            search_lobby_history,
+            delete_lobby_history_entry,
+            clear_lobby_history,
+            export_lobby_history,
+            import_lobby_history,
+            get_player_notes,
+            save_player_note,
+            delete_player_note,
+            get_teammate_stats,
+            set_log_level,
             write_frontend_log
         ])
         .run(tauri::generate_context!());
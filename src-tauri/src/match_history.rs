@@ -0,0 +1,128 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// How many recent games are read per player.
+pub const RECENT_GAMES: usize = 20;
+
+/// `/lol-match-history/v1/products/lol/{puuid}/matches`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchHistory {
+    #[serde(default)]
+    pub games: GamePage,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GamePage {
+    /// Most recent first.
+    #[serde(default)]
+    pub games: Vec<Game>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Game {
+    #[serde(default)]
+    pub participants: Vec<GameParticipant>,
+    #[serde(default)]
+    pub participant_identities: Vec<ParticipantIdentity>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameParticipant {
+    pub participant_id: u32,
+    pub champion_id: u32,
+    #[serde(default)]
+    pub stats: ParticipantStats,
+    #[serde(default)]
+    pub timeline: ParticipantTimeline,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ParticipantStats {
+    #[serde(default)]
+    pub win: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ParticipantTimeline {
+    #[serde(default)]
+    pub lane: String,
+    #[serde(default)]
+    pub role: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantIdentity {
+    pub participant_id: u32,
+    #[serde(default)]
+    pub player: IdentityPlayer,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct IdentityPlayer {
+    #[serde(default)]
+    pub puuid: String,
+}
+
+impl Game {
+    /// The stats of `puuid` in this game. A player's own history usually only
+    /// lists them, so the only participant is used when nobody matches.
+    pub fn player(&self, puuid: &str) -> Option<&GameParticipant> {
+        let participant_id = self
+            .participant_identities
+            .iter()
+            .find(|identity| identity.player.puuid == puuid)
+            .map(|identity| identity.participant_id);
+
+        match participant_id {
+            Some(participant_id) => self
+                .participants
+                .iter()
+                .find(|participant| participant.participant_id == participant_id),
+            None if self.participants.len() == 1 => self.participants.first(),
+            None => None,
+        }
+    }
+}
+
+impl GameParticipant {
+    /// The position played, using Champ Select's names (`top`, `jungle`,
+    /// `middle`, `bottom`, `utility`), or `None` outside Summoner's Rift lanes.
+    pub fn position(&self) -> Option<&'static str> {
+        match (self.timeline.lane.as_str(), self.timeline.role.as_str()) {
+            ("TOP", _) => Some("top"),
+            ("JUNGLE", _) => Some("jungle"),
+            ("MIDDLE" | "MID", _) => Some("middle"),
+            ("BOTTOM" | "BOT", "DUO_SUPPORT" | "SUPPORT") => Some("utility"),
+            ("BOTTOM" | "BOT", _) => Some("bottom"),
+            _ => None,
+        }
+    }
+}
+
+/// `/lol-ranked/v1/ranked-stats/{puuid}`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedStats {
+    #[serde(default)]
+    pub queue_map: HashMap<String, RankedQueue>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedQueue {
+    #[serde(default)]
+    pub tier: String,
+    #[serde(default)]
+    pub division: String,
+    #[serde(default)]
+    pub league_points: u32,
+    #[serde(default)]
+    pub wins: u32,
+    #[serde(default)]
+    pub losses: u32,
+}
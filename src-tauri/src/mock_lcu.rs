@@ -0,0 +1,425 @@
+//! Scriptable stand-in for the League Client.
+//!
+//! Serves the REST resources Reveal reads over HTTPS and pushes
+//! `OnJsonApiEvent` updates over the same WAMP websocket the real client
+//! exposes. A recorded [`Timeline`] can be replayed against it to drive Reveal
+//! through a full champ select. This file is shared by the test suite and the
+//! `mock-lcu` binary, so it must not depend on the rest of the crate.
+#![allow(dead_code)]
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpListener,
+    sync::broadcast,
+};
+use tokio_native_tls::{native_tls, TlsAcceptor};
+use tokio_tungstenite::{
+    tungstenite::{handshake::derive_accept_key, protocol::Role, Message},
+    WebSocketStream,
+};
+
+pub const DEFAULT_AUTH_TOKEN: &str = "mock-lcu-token";
+
+const CERTIFICATE: &[u8] = include_bytes!("../fixtures/mock-lcu/cert.pem");
+const PRIVATE_KEY: &[u8] = include_bytes!("../fixtures/mock-lcu/key.pem");
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const SUBSCRIPTION_WAIT: Duration = Duration::from_secs(5);
+
+/// A recorded sequence of League Client resource changes.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Timeline {
+    /// Resources that exist before the first step, served without events.
+    #[serde(default)]
+    pub resources: HashMap<String, Value>,
+    #[serde(default)]
+    pub steps: Vec<TimelineStep>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineStep {
+    /// Time to wait after the previous step.
+    #[serde(default)]
+    pub delay_ms: u64,
+    pub uri: String,
+    /// New resource body; `null` deletes the resource.
+    #[serde(default)]
+    pub data: Value,
+}
+
+impl Timeline {
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("failed to parse mock LCU timeline")
+    }
+}
+
+/// A non-GET request the mock received, kept so tests can assert on it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub body: Value,
+}
+
+#[derive(Clone, Debug)]
+struct ResourceEvent {
+    uri: String,
+    event_type: &'static str,
+    data: Value,
+}
+
+struct MockState {
+    auth_header: String,
+    resources: Mutex<HashMap<String, Value>>,
+    requests: Mutex<Vec<RecordedRequest>>,
+    subscriptions: Mutex<HashSet<String>>,
+    events: broadcast::Sender<ResourceEvent>,
+}
+
+pub struct MockLcu {
+    port: u16,
+    auth_token: String,
+    state: Arc<MockState>,
+}
+
+impl MockLcu {
+    /// Starts a mock on a random local port.
+    pub async fn start() -> Result<Self> {
+        Self::bind(0, DEFAULT_AUTH_TOKEN).await
+    }
+
+    pub async fn bind(port: u16, auth_token: &str) -> Result<Self> {
+        let identity = native_tls::Identity::from_pkcs8(CERTIFICATE, PRIVATE_KEY)
+            .context("failed to load the mock LCU certificate")?;
+        let acceptor = TlsAcceptor::from(
+            native_tls::TlsAcceptor::new(identity).context("failed to build TLS acceptor")?,
+        );
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .context("failed to bind the mock LCU port")?;
+        let port = listener.local_addr()?.port();
+
+        let (events, _) = broadcast::channel(64);
+        let state = Arc::new(MockState {
+            auth_header: format!("Basic {}", STANDARD.encode(format!("riot:{auth_token}"))),
+            resources: Mutex::new(default_resources()),
+            requests: Mutex::new(Vec::new()),
+            subscriptions: Mutex::new(HashSet::new()),
+            events,
+        });
+
+        let server_state = state.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                let state = server_state.clone();
+                tokio::spawn(async move {
+                    let Ok(stream) = acceptor.accept(stream).await else {
+                        return;
+                    };
+                    let _ = handle_connection(stream, state).await;
+                });
+            }
+        });
+
+        Ok(Self {
+            port,
+            auth_token: auth_token.to_string(),
+            state,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn auth_token(&self) -> &str {
+        &self.auth_token
+    }
+
+    /// An HTTP client that trusts the mock's certificate and sends its
+    /// credentials.
+    pub fn client(&self) -> reqwest::Client {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::AUTHORIZATION,
+            self.state
+                .auth_header
+                .parse()
+                .expect("auth header is ASCII"),
+        );
+        reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .default_headers(headers)
+            .build()
+            .expect("failed to build the mock LCU client")
+    }
+
+    /// Replaces a resource and notifies websocket subscribers.
+    pub fn set(&self, uri: &str, data: Value) {
+        let event_type = if data.is_null() {
+            lock(&self.state.resources).remove(uri);
+            "Delete"
+        } else {
+            lock(&self.state.resources).insert(uri.to_string(), data.clone());
+            "Update"
+        };
+
+        let _ = self.state.events.send(ResourceEvent {
+            uri: uri.to_string(),
+            event_type,
+            data,
+        });
+    }
+
+    pub async fn replay(&self, timeline: &Timeline) {
+        lock(&self.state.resources).extend(timeline.resources.clone());
+
+        for step in &timeline.steps {
+            tokio::time::sleep(Duration::from_millis(step.delay_ms)).await;
+            self.set(&step.uri, step.data.clone());
+        }
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        lock(&self.state.requests).clone()
+    }
+
+    /// Waits until a websocket client has subscribed to `uri`.
+    pub async fn wait_for_subscription(&self, uri: &str) -> Result<()> {
+        let topic = topic_for(uri);
+        let started_at = tokio::time::Instant::now();
+        while !lock(&self.state.subscriptions).contains(&topic) {
+            if started_at.elapsed() >= SUBSCRIPTION_WAIT {
+                bail!("no websocket client subscribed to {uri}");
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        Ok(())
+    }
+}
+
+fn default_resources() -> HashMap<String, Value> {
+    HashMap::from([
+        ("/lol-gameflow/v1/gameflow-phase".to_string(), json!("None")),
+        (
+            "/riotclient/region-locale".to_string(),
+            json!({
+                "locale": "en_US",
+                "region": "NA",
+                "webLanguage": "en",
+                "webRegion": "NA"
+            }),
+        ),
+        (
+            "/chat/v5/participants".to_string(),
+            json!({ "participants": [] }),
+        ),
+    ])
+}
+
+fn topic_for(uri: &str) -> String {
+    format!("OnJsonApiEvent{}", uri.replace('/', "_"))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+async fn handle_connection<S>(mut stream: S, state: Arc<MockState>) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let request = read_request(&mut stream).await?;
+
+    if request.headers.get("authorization") != Some(&state.auth_header) {
+        return write_response(&mut stream, "401 Unauthorized", None).await;
+    }
+
+    let is_upgrade = request
+        .headers
+        .get("upgrade")
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+    if is_upgrade {
+        return serve_websocket(stream, &request, state).await;
+    }
+
+    let path = request.path.split('?').next().unwrap_or_default();
+    if request.method == "GET" {
+        let resource = lock(&state.resources).get(path).cloned();
+        return match resource {
+            Some(body) => write_response(&mut stream, "200 OK", Some(&body)).await,
+            None => {
+                let error = json!({
+                    "errorCode": "RPC_ERROR",
+                    "httpStatus": 404,
+                    "message": format!("No resource at {path}")
+                });
+                write_response(&mut stream, "404 Not Found", Some(&error)).await
+            }
+        };
+    }
+
+    lock(&state.requests).push(RecordedRequest {
+        method: request.method,
+        path: request.path,
+        body: serde_json::from_slice(&request.body).unwrap_or(Value::Null),
+    });
+    write_response(&mut stream, "204 No Content", None).await
+}
+
+async fn read_request<S>(stream: &mut S) -> Result<Request>
+where
+    S: AsyncRead + Unpin,
+{
+    let mut buffer = Vec::new();
+    let header_end = loop {
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position + 4;
+        }
+        if buffer.len() > MAX_HEADER_BYTES {
+            bail!("request headers are too large");
+        }
+
+        let mut chunk = [0; 1024];
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            bail!("connection closed before the request was complete");
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .get("content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or_default();
+    let mut body = buffer[header_end..].to_vec();
+    while body.len() < content_length {
+        let mut chunk = vec![0; content_length - body.len()];
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            bail!("connection closed before the request body was complete");
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+async fn write_response<S>(stream: &mut S, status: &str, body: Option<&Value>) -> Result<()>
+where
+    S: AsyncWrite + Unpin,
+{
+    let body = body.map(Value::to_string).unwrap_or_default();
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+async fn serve_websocket<S>(mut stream: S, request: &Request, state: Arc<MockState>) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let key = request
+        .headers
+        .get("sec-websocket-key")
+        .context("websocket upgrade is missing its key")?;
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.as_bytes())
+    );
+    stream.write_all(response.as_bytes()).await?;
+
+    let mut socket = WebSocketStream::from_raw_socket(stream, Role::Server, None).await;
+    let mut events = state.events.subscribe();
+    let mut topics = HashSet::new();
+
+    loop {
+        tokio::select! {
+            message = socket.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                let Ok((opcode, topic)) = serde_json::from_str::<(u8, String)>(&text) else {
+                    continue;
+                };
+                match opcode {
+                    5 => {
+                        lock(&state.subscriptions).insert(topic.clone());
+                        topics.insert(topic);
+                    }
+                    6 => {
+                        topics.remove(&topic);
+                    }
+                    _ => {}
+                }
+            }
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let topic = topic_for(&event.uri);
+                if !topics.contains(&topic) {
+                    continue;
+                }
+
+                let message = json!([
+                    8,
+                    topic,
+                    {
+                        "data": event.data,
+                        "eventType": event.event_type,
+                        "uri": event.uri
+                    }
+                ]);
+                socket.send(Message::Text(message.to_string())).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
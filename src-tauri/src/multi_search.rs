@@ -0,0 +1,378 @@
+use crate::{
+    config::{Config, CustomProvider},
+    lobby::Participant,
+    region::Region,
+};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use urlencoding::encode;
+
+/// A site that looks up several players at once.
+pub trait MultiSearchProvider: Send + Sync {
+    /// The key stored in `Config::multi_provider`.
+    fn id(&self) -> &str;
+    fn name(&self) -> &str;
+    /// Fails if the site has no pages for `region`.
+    fn link(&self, participants: &[Participant], region: &Region) -> Result<String>;
+}
+
+/// Built-in providers as `(id, name, URL template, tag separator)`.
+const BUILTIN_PROVIDERS: [(&str, &str, &str, &str); 5] = [
+    (
+        "opgg",
+        "OP.GG",
+        "https://www.op.gg/multisearch/{region}?summoners={riotIds}",
+        "#",
+    ),
+    (
+        "deeplol",
+        "DeepLoL",
+        "https://deeplol.gg/multi/{region}/{riotIds}",
+        "#",
+    ),
+    (
+        "ugg",
+        "U.GG",
+        "https://u.gg/multisearch?region={region}&summoners={riotIds}",
+        "-",
+    ),
+    (
+        "tracker",
+        "Tracker.gg",
+        "https://tracker.gg/lol/multisearch/{region}/{riotIds}",
+        "#",
+    ),
+    (
+        "fow",
+        "FOW.LOL",
+        "https://www.fow.lol/multi/{region}#{riotIds}",
+        "#",
+    ),
+];
+
+/// What the settings screen shows for a provider.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderInfo {
+    pub id: String,
+    pub name: String,
+    pub custom: bool,
+}
+
+/// The built-in providers followed by the user's own.
+pub struct Providers {
+    providers: Vec<Box<dyn MultiSearchProvider>>,
+    builtin_count: usize,
+}
+
+impl Providers {
+    pub fn builtin() -> Self {
+        let providers: Vec<Box<dyn MultiSearchProvider>> = BUILTIN_PROVIDERS
+            .iter()
+            .map(|&(id, name, url, tag_separator)| {
+                let template = UrlTemplate::parse(url, tag_separator, ",")
+                    .expect("built-in templates are valid");
+                Box::new(TemplateProvider {
+                    id: id.to_string(),
+                    name: name.to_string(),
+                    template,
+                }) as Box<dyn MultiSearchProvider>
+            })
+            .collect();
+
+        Self {
+            builtin_count: providers.len(),
+            providers,
+        }
+    }
+
+    /// Adds the custom providers from `config`, rejecting invalid templates
+    /// and IDs that are empty or already taken.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut providers = Self::builtin();
+        for custom in &config.custom_providers {
+            providers.add_custom(custom)?;
+        }
+
+        Ok(providers)
+    }
+
+    pub fn add_custom(&mut self, custom: &CustomProvider) -> Result<()> {
+        let id = custom.id.trim();
+        if id.is_empty() {
+            bail!("custom multi-search providers need an ID");
+        }
+        if self.get(id).is_some() {
+            bail!("multi-search provider {id} is defined more than once");
+        }
+        self.register(TemplateProvider::custom(custom)?);
+        Ok(())
+    }
+
+    pub fn register(&mut self, provider: impl MultiSearchProvider + 'static) {
+        self.providers.push(Box::new(provider));
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn MultiSearchProvider> {
+        self.providers
+            .iter()
+            .find(|provider| provider.id() == id)
+            .map(Box::as_ref)
+    }
+
+    pub fn list(&self) -> Vec<ProviderInfo> {
+        self.providers
+            .iter()
+            .enumerate()
+            .map(|(index, provider)| ProviderInfo {
+                id: provider.id().to_string(),
+                name: provider.name().to_string(),
+                custom: index >= self.builtin_count,
+            })
+            .collect()
+    }
+}
+
+/// The provider selected in `config`.
+pub fn selected(config: &Config) -> Result<Box<dyn MultiSearchProvider>> {
+    let Providers { providers, .. } = Providers::from_config(config)?;
+    providers
+        .into_iter()
+        .find(|provider| provider.id() == config.multi_provider)
+        .with_context(|| format!("unknown multi-search provider: {}", config.multi_provider))
+}
+
+struct TemplateProvider {
+    id: String,
+    name: String,
+    template: UrlTemplate,
+}
+
+impl TemplateProvider {
+    fn custom(custom: &CustomProvider) -> Result<Self> {
+        let id = custom.id.trim();
+        let template =
+            UrlTemplate::parse(&custom.url, &custom.tag_separator, &custom.list_separator)
+                .with_context(|| format!("invalid URL template for multi-search provider {id}"))?;
+        let name = match custom.name.trim() {
+            "" => id,
+            name => name,
+        };
+
+        Ok(Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            template,
+        })
+    }
+}
+
+impl MultiSearchProvider for TemplateProvider {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn link(&self, participants: &[Participant], region: &Region) -> Result<String> {
+        let slug = region
+            .slug(&self.id)
+            .with_context(|| format!("{} has no pages for {region}", self.name))?;
+        Ok(self
+            .template
+            .render(participants, &slug, region.platform_id()))
+    }
+}
+
+/// A provider URL with `{region}`, `{platform}` and `{riotIds}` placeholders.
+/// `{region}` is the provider's name for the server and `{platform}` its
+/// platform ID, e.g. `EUN1`. Any of them can end in `:lower` or `:upper` to
+/// change its case, e.g. `{region:lower}`.
+struct UrlTemplate {
+    parts: Vec<TemplatePart>,
+    tag_separator: String,
+    list_separator: String,
+}
+
+enum TemplatePart {
+    Text(String),
+    Region(Case),
+    Platform(Case),
+    RiotIds(Case),
+}
+
+#[derive(Clone, Copy)]
+enum Case {
+    Unchanged,
+    Lower,
+    Upper,
+}
+
+impl Case {
+    fn apply(self, value: &str) -> String {
+        match self {
+            Case::Unchanged => value.to_string(),
+            Case::Lower => value.to_lowercase(),
+            Case::Upper => value.to_uppercase(),
+        }
+    }
+}
+
+impl UrlTemplate {
+    fn parse(url: &str, tag_separator: &str, list_separator: &str) -> Result<Self> {
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            bail!("the URL must start with https:// or http://");
+        }
+        if tag_separator.is_empty() || list_separator.is_empty() {
+            bail!("separators cannot be empty");
+        }
+
+        let mut parts = Vec::new();
+        let mut rest = url;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(TemplatePart::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .map(|end| start + end)
+                .context("unclosed placeholder")?;
+            let placeholder = &rest[start + 1..end];
+            let (name, case) = match placeholder.split_once(':') {
+                Some((name, "lower")) => (name, Case::Lower),
+                Some((name, "upper")) => (name, Case::Upper),
+                Some((_, case)) => bail!("unknown case transform: {case}"),
+                None => (placeholder, Case::Unchanged),
+            };
+            parts.push(match name {
+                "region" => TemplatePart::Region(case),
+                "platform" => TemplatePart::Platform(case),
+                "riotIds" => TemplatePart::RiotIds(case),
+                _ => bail!("unknown placeholder: {{{name}}}"),
+            });
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Text(rest.to_string()));
+        }
+
+        if !parts
+            .iter()
+            .any(|part| matches!(part, TemplatePart::RiotIds(_)))
+        {
+            bail!("the URL needs a {{riotIds}} placeholder");
+        }
+
+        Ok(Self {
+            parts,
+            tag_separator: tag_separator.to_string(),
+            list_separator: list_separator.to_string(),
+        })
+    }
+
+    fn render(&self, participants: &[Participant], region: &str, platform_id: &str) -> String {
+        let riot_ids = participants
+            .iter()
+            .map(|participant| {
+                format!(
+                    "{}{}{}",
+                    participant.game_name, self.tag_separator, participant.game_tag
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(&self.list_separator);
+
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => text.clone(),
+                TemplatePart::Region(case) => case.apply(region),
+                TemplatePart::Platform(case) => case.apply(platform_id),
+                TemplatePart::RiotIds(case) => encode(&case.apply(&riot_ids)).into_owned(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(id: &str, url: &str) -> CustomProvider {
+        CustomProvider {
+            id: id.to_string(),
+            name: String::new(),
+            url: url.to_string(),
+            tag_separator: "-".to_string(),
+            list_separator: "|".to_string(),
+        }
+    }
+
+    fn config(provider: &str, custom_providers: Vec<CustomProvider>) -> Config {
+        Config {
+            multi_provider: provider.to_string(),
+            custom_providers,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn renders_custom_templates() {
+        let config = config(
+            "mine",
+            vec![custom(
+                "mine",
+                "https://example.com/{region:lower}/{riotIds:lower}?p={platform}",
+            )],
+        );
+        let provider = selected(&config).unwrap();
+        let participants = [
+            Participant::from_riot_id("Player One", "NA1"),
+            Participant::from_riot_id("Two", "0911"),
+        ];
+
+        assert_eq!(provider.name(), "mine");
+        assert_eq!(
+            provider.link(&participants, &Region::Eune).unwrap(),
+            "https://example.com/eune/player%20one-na1%7Ctwo-0911?p=EUN1"
+        );
+        assert_eq!(
+            provider.link(&participants, &Region::Pbe).unwrap(),
+            "https://example.com/pbe/player%20one-na1%7Ctwo-0911?p=PBE1"
+        );
+        assert_eq!(
+            Providers::from_config(&config).unwrap().list().last(),
+            Some(&ProviderInfo {
+                id: "mine".to_string(),
+                name: "mine".to_string(),
+                custom: true,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_providers_and_invalid_templates() {
+        assert!(selected(&Config::default()).is_ok());
+        assert!(selected(&config("unknown", vec![])).is_err());
+
+        for url in [
+            "example.com/{riotIds}",
+            "https://example.com/{region}",
+            "https://example.com/{riotIds",
+            "https://example.com/{summoners}",
+            "https://example.com/{riotIds:title}",
+        ] {
+            assert!(
+                selected(&config("mine", vec![custom("mine", url)])).is_err(),
+                "{url}"
+            );
+        }
+
+        let valid = custom("mine", "https://example.com/{riotIds}");
+        assert!(selected(&config("mine", vec![valid.clone(), valid.clone()])).is_err());
+        assert!(selected(&config("opgg", vec![custom("opgg", &valid.url)])).is_err());
+        assert!(selected(&config("", vec![custom(" ", &valid.url)])).is_err());
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{
+    lobby::Lobby,
+    store::{self, Dir},
+};
+use anyhow::{bail, Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+const NOTES_FILE: &str = "player_notes.json";
+
+/// What the user wrote down about a player.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PlayerNote {
+    pub puuid: String,
+    /// The player's `name#tag` when the note was saved, for the notes list.
+    #[serde(default)]
+    pub riot_id: String,
+    #[serde(default)]
+    pub note: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// RFC 3339 timestamp.
+    pub updated_at: String,
+}
+
+/// Player notes keyed by puuid, saved as a JSON array under the app data
+/// directory.
+#[derive(Clone)]
+pub struct PlayerNotes(Arc<Mutex<NotesStore>>);
+
+struct NotesStore {
+    /// `None` keeps the notes in memory, when the file could not be loaded.
+    path: Option<PathBuf>,
+    notes: HashMap<String, PlayerNote>,
+}
+
+impl PlayerNotes {
+    pub fn load(app_handle: &AppHandle) -> Result<Self> {
+        Self::open(store::path(app_handle, Dir::Data, NOTES_FILE)?)
+    }
+
+    /// Notes that are not saved, for when [`load`](Self::load) fails.
+    pub fn in_memory() -> Self {
+        Self(Arc::new(Mutex::new(NotesStore {
+            path: None,
+            notes: HashMap::new(),
+        })))
+    }
+
+    /// Reads the notes at `path`. A file that cannot be parsed is kept next
+    /// to it as a backup and the notes start empty.
+    fn open(path: PathBuf) -> Result<Self> {
+        let notes = if path.exists() {
+            read(&path)?
+        } else {
+            Vec::new()
+        };
+        log_info!("Loaded {} player notes", notes.len());
+
+        let notes = notes
+            .into_iter()
+            .map(|note| (note.puuid.clone(), note))
+            .collect();
+        Ok(Self(Arc::new(Mutex::new(NotesStore {
+            path: Some(path),
+            notes,
+        }))))
+    }
+
+    /// Every note, most recently updated first.
+    pub async fn list(&self) -> Vec<PlayerNote> {
+        let store = self.0.lock().await;
+        let mut notes: Vec<_> = store.notes.values().cloned().collect();
+        notes.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        notes
+    }
+
+    /// Creates or replaces the note for `puuid`. Tags are trimmed and
+    /// deduplicated, ignoring case.
+    pub async fn save(
+        &self,
+        puuid: &str,
+        riot_id: &str,
+        note: &str,
+        tags: &[String],
+    ) -> Result<PlayerNote> {
+        if puuid.is_empty() {
+            bail!("a note needs the player's puuid");
+        }
+
+        let note = PlayerNote {
+            puuid: puuid.to_string(),
+            riot_id: riot_id.trim().to_string(),
+            note: note.trim().to_string(),
+            tags: normalize_tags(tags),
+            updated_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        };
+        let mut store = self.0.lock().await;
+        let previous = store.notes.insert(note.puuid.clone(), note.clone());
+        if let Err(error) = store.write().await {
+            match previous {
+                Some(previous) => store.notes.insert(previous.puuid.clone(), previous),
+                None => store.notes.remove(puuid),
+            };
+            return Err(error);
+        }
+
+        Ok(note)
+    }
+
+    /// Removes the note for `puuid`, returning whether there was one.
+    pub async fn delete(&self, puuid: &str) -> Result<bool> {
+        let mut store = self.0.lock().await;
+        let Some(previous) = store.notes.remove(puuid) else {
+            return Ok(false);
+        };
+        if let Err(error) = store.write().await {
+            store.notes.insert(previous.puuid.clone(), previous);
+            return Err(error);
+        }
+
+        Ok(true)
+    }
+
+    /// Attaches the saved note to every participant that has one.
+    pub async fn annotate(&self, lobby: &mut Lobby) {
+        let store = self.0.lock().await;
+        for participant in &mut lobby.participants {
+            participant.note = store.notes.get(&participant.puuid).cloned();
+        }
+    }
+}
+
+impl NotesStore {
+    async fn write(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut notes: Vec<_> = self.notes.values().collect();
+        notes.sort_by(|a, b| a.puuid.cmp(&b.puuid));
+        let json = serde_json::to_string_pretty(&notes).context("failed to serialize notes")?;
+        store::write(path, json).await
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<PlayerNote>> {
+    let json = std::fs::read_to_string(path).context("failed to read player notes")?;
+    match serde_json::from_str(&json) {
+        Ok(notes) => Ok(notes),
+        Err(error) => {
+            log_error!("Player notes are corrupt, starting without notes: {error}");
+            let backup_path = store::back_up(path, "corrupt")?;
+            log_info!(
+                "Backed up the corrupt player notes to {}",
+                backup_path.display()
+            );
+            Ok(Vec::new())
+        }
+    }
+}
+
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags
+        .iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty())
+    {
+        if !normalized
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(tag))
+        {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+impl PlayerNotes {
+    /// Notes backed by a fresh file in the system temp directory.
+    pub fn temporary() -> Self {
+        Self::open(store::temporary_dir("player-notes").join(NOTES_FILE)).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lobby::Participant;
+
+    #[tokio::test]
+    async fn saves_notes_and_attaches_them_to_matching_players() {
+        let notes = PlayerNotes::temporary();
+        let tags = ["toxic", " Toxic ", "", "smurf?"].map(String::from);
+        let saved = notes
+            .save("griefer-puuid", "Griefer#NA1", " ran it down ", &tags)
+            .await
+            .unwrap();
+        assert_eq!(saved.note, "ran it down");
+        assert_eq!(saved.tags, ["toxic", "smurf?"]);
+
+        let path = notes.0.lock().await.path.clone().unwrap();
+        let reopened = PlayerNotes::open(path).unwrap();
+        let mut lobby = Lobby {
+            participants: vec![
+                Participant {
+                    puuid: "griefer-puuid".to_string(),
+                    ..Participant::from_riot_id("Griefer", "NA1")
+                },
+                Participant {
+                    puuid: "other-puuid".to_string(),
+                    ..Participant::from_riot_id("Other", "NA1")
+                },
+            ],
+        };
+        reopened.annotate(&mut lobby).await;
+
+        assert_eq!(lobby.participants[0].note.as_ref(), Some(&saved));
+        assert_eq!(lobby.participants[1].note, None);
+
+        assert!(reopened.delete("griefer-puuid").await.unwrap());
+        assert!(!reopened.delete("griefer-puuid").await.unwrap());
+        assert!(reopened.list().await.is_empty());
+        assert!(reopened.save("", "", "note", &[]).await.is_err());
+    }
+
+    #[test]
+    fn backs_up_a_corrupt_file_and_starts_empty() {
+        let dir = store::temporary_dir("player-notes");
+        let path = dir.join(NOTES_FILE);
+        std::fs::write(&path, r#"[{"puuid": "#).unwrap();
+
+        let notes = PlayerNotes::open(path.clone()).unwrap();
+
+        assert!(notes.0.try_lock().unwrap().notes.is_empty());
+        assert!(!path.exists());
+        let backups = std::fs::read_dir(&dir).unwrap().count();
+        assert_eq!(backups, 1);
+    }
+}
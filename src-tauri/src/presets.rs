@@ -0,0 +1,261 @@
+use crate::{
+    champ_select::{self, SessionEvent, SESSION_URI},
+    event_bus::EventBus,
+    lcu_api::LcuApi,
+    logging,
+    state::PhaseContext,
+    store::{self, Dir},
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+
+const PRESETS_FILE: &str = "presets.json";
+/// Prefix that marks rune pages Reveal manages and may replace.
+const RUNE_PAGE_PREFIX: &str = "Reveal: ";
+
+/// Summoner spells and runes to apply when a champion is locked in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Preset {
+    pub champion_id: u32,
+    /// Assigned position this preset is for; `None` applies to every
+    /// position without a more specific preset.
+    #[serde(default)]
+    pub position: Option<String>,
+    #[serde(default)]
+    pub spells: Option<[u64; 2]>,
+    #[serde(default)]
+    pub rune_page: Option<RunePage>,
+}
+
+/// Rune page as accepted by `/lol-perks/v1/pages`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunePage {
+    pub name: String,
+    pub primary_style_id: u32,
+    pub sub_style_id: u32,
+    pub selected_perk_ids: Vec<u32>,
+}
+
+/// Existing rune page as listed by `/lol-perks/v1/pages`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunePageSummary {
+    pub id: u64,
+    pub name: String,
+    #[serde(default)]
+    pub is_deletable: bool,
+}
+
+pub struct AppPresets(pub Mutex<Vec<Preset>>);
+
+impl AppPresets {
+    pub fn new(presets: Vec<Preset>) -> Self {
+        Self(Mutex::new(presets))
+    }
+}
+
+fn presets_path(app_handle: &AppHandle) -> Result<PathBuf> {
+    store::path(app_handle, Dir::Config, PRESETS_FILE)
+}
+
+pub fn load(app_handle: &AppHandle) -> Result<Vec<Preset>> {
+    let presets_path = presets_path(app_handle)?;
+    if !presets_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let json = std::fs::read_to_string(&presets_path).context("failed to read presets")?;
+    let presets = parse(&json)?;
+    log_info!("Loaded {} champion presets", presets.len());
+    Ok(presets)
+}
+
+pub async fn save(app_handle: &AppHandle, presets: &[Preset]) -> Result<()> {
+    store::write(&presets_path(app_handle)?, export(presets)?).await?;
+    log_info!("Saved {} champion presets", presets.len());
+    Ok(())
+}
+
+pub fn parse(json: &str) -> Result<Vec<Preset>> {
+    serde_json::from_str(json).context("failed to parse presets")
+}
+
+pub fn export(presets: &[Preset]) -> Result<String> {
+    serde_json::to_string_pretty(presets).context("failed to serialize presets")
+}
+
+/// Adds `imported` to `presets`, replacing presets for the same champion and
+/// position.
+pub fn merge(presets: &mut Vec<Preset>, imported: Vec<Preset>) {
+    for preset in imported {
+        presets.retain(|existing| {
+            existing.champion_id != preset.champion_id || existing.position != preset.position
+        });
+        presets.push(preset);
+    }
+}
+
+/// The preset for `champion_id` in `position`, preferring one made for that
+/// position over a general one.
+fn find<'a>(presets: &'a [Preset], champion_id: u32, position: &str) -> Option<&'a Preset> {
+    let for_champion = || presets.iter().filter(move |p| p.champion_id == champion_id);
+
+    for_champion()
+        .find(|preset| preset.position.as_deref() == Some(position))
+        .or_else(|| for_champion().find(|preset| preset.position.is_none()))
+}
+
+/// Applies presets once per game, as soon as the local player locks in.
+pub fn register(bus: &EventBus, context: &impl PhaseContext, lcu: &impl LcuApi) {
+    let mut sessions = bus.subscribe(SESSION_URI);
+    let context = context.clone();
+    let lcu = lcu.clone();
+
+    tauri::async_runtime::spawn(logging::inherit(async move {
+        let mut applied = None;
+        while let Some(event) = champ_select::next_session(&mut sessions).await {
+            let SessionEvent::Update(session) = event else {
+                continue;
+            };
+            let Some(champion_id) = session.locked_champion() else {
+                continue;
+            };
+            if applied == Some((session.game_id, champion_id)) {
+                continue;
+            }
+            applied = Some((session.game_id, champion_id));
+
+            let presets = context.presets().0.lock().await.clone();
+            apply(&lcu, &presets, champion_id, session.local_position()).await;
+        }
+    }));
+}
+
+async fn apply(lcu: &impl LcuApi, presets: &[Preset], champion_id: u32, position: &str) {
+    let Some(preset) = find(presets, champion_id, position) else {
+        return;
+    };
+    log_info!("Applying the preset for champion {champion_id} ({position})");
+
+    if let Some([spell1_id, spell2_id]) = preset.spells {
+        if let Err(error) = lcu.set_summoner_spells(spell1_id, spell2_id).await {
+            log_error!("Failed to set summoner spells: {error}");
+        }
+    }
+
+    if let Some(rune_page) = &preset.rune_page {
+        if let Err(error) = replace_rune_page(lcu, rune_page).await {
+            log_error!("Failed to import rune page: {error:#}");
+        }
+    }
+}
+
+/// Creates `rune_page`, first deleting the page Reveal created last time so
+/// pages do not pile up.
+async fn replace_rune_page(lcu: &impl LcuApi, rune_page: &RunePage) -> Result<()> {
+    let page = RunePage {
+        name: format!("{RUNE_PAGE_PREFIX}{}", rune_page.name),
+        ..rune_page.clone()
+    };
+
+    let pages = lcu
+        .rune_pages()
+        .await
+        .context("failed to list rune pages")?;
+    for existing in pages
+        .iter()
+        .filter(|existing| existing.is_deletable && existing.name.starts_with(RUNE_PAGE_PREFIX))
+    {
+        lcu.delete_rune_page(existing.id)
+            .await
+            .with_context(|| format!("failed to delete rune page {}", existing.id))?;
+    }
+
+    lcu.create_rune_page(&page)
+        .await
+        .context("failed to create rune page")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lcu_api::fake::FakeLcuApi;
+
+    const PRESETS: &str = r#"[
+        {
+            "championId": 64,
+            "spells": [4, 11],
+            "runePage": {
+                "name": "Lee Sin",
+                "primaryStyleId": 8000,
+                "subStyleId": 8100,
+                "selectedPerkIds": [8010, 9111, 9104, 8299, 8143, 8135, 5008, 5008, 5001]
+            }
+        },
+        { "championId": 64, "position": "top", "spells": [4, 12] }
+    ]"#;
+
+    #[test]
+    fn prefers_the_preset_for_the_assigned_position() {
+        let presets = parse(PRESETS).unwrap();
+
+        assert_eq!(find(&presets, 64, "top").unwrap().spells, Some([4, 12]));
+        assert_eq!(find(&presets, 64, "jungle").unwrap().spells, Some([4, 11]));
+        assert_eq!(find(&presets, 64, "").unwrap().spells, Some([4, 11]));
+        assert_eq!(find(&presets, 103, "middle"), None);
+    }
+
+    #[test]
+    fn imports_replace_presets_for_the_same_champion_and_position() {
+        let mut presets = parse(PRESETS).unwrap();
+
+        merge(
+            &mut presets,
+            vec![Preset {
+                champion_id: 64,
+                position: Some("top".to_string()),
+                spells: Some([4, 14]),
+                rune_page: None,
+            }],
+        );
+
+        assert_eq!(presets.len(), 2);
+        assert_eq!(find(&presets, 64, "top").unwrap().spells, Some([4, 14]));
+        assert_eq!(parse(&export(&presets).unwrap()).unwrap(), presets);
+    }
+
+    #[tokio::test]
+    async fn sets_spells_and_replaces_the_previous_reveal_rune_page() {
+        let lcu = FakeLcuApi::default();
+        lcu.state().rune_pages = vec![
+            RunePageSummary {
+                id: 1,
+                name: "Reveal: Ahri".to_string(),
+                is_deletable: true,
+            },
+            RunePageSummary {
+                id: 2,
+                name: "My page".to_string(),
+                is_deletable: true,
+            },
+        ];
+
+        apply(&lcu, &parse(PRESETS).unwrap(), 64, "jungle").await;
+
+        let state = lcu.state();
+        assert_eq!(state.summoner_spells, Some((4, 11)));
+        assert_eq!(state.actions, ["delete_rune_page"]);
+        let names: Vec<_> = state
+            .rune_pages
+            .iter()
+            .map(|page| page.name.as_str())
+            .collect();
+        assert_eq!(names, ["My page", "Reveal: Lee Sin"]);
+        assert_eq!(state.created_rune_pages[0].primary_style_id, 8000);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{
+    config::{ActiveAccount, AppConfig},
+    emitter::Emitter,
+    event_bus::{self, EventBus},
+    lcu_api::LcuApi,
+    logging,
+    state::PhaseContext,
+    summoner::Summoner,
+    websocket::LcuEvent,
+};
+use anyhow::Result;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+pub const CURRENT_SUMMONER_URI: &str = "/lol-summoner/v1/current-summoner";
+
+/// Which account is logged in and the profile that applies to it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveProfile {
+    pub riot_id: String,
+    /// `None` when no profile lists the account.
+    pub profile: Option<String>,
+}
+
+pub async fn active_profile(config: &AppConfig, account: &ActiveAccount) -> Option<ActiveProfile> {
+    let account = account.0.lock().await.clone()?;
+    let config = config.0.lock().await;
+    Some(ActiveProfile {
+        riot_id: account.riot_id(),
+        profile: config
+            .profile_for(&account)
+            .map(|profile| profile.name.clone()),
+    })
+}
+
+/// Follows the logged-in account for as long as the client is connected, so
+/// logging in after the client opens still applies the right profile.
+pub fn register(bus: &EventBus, context: &impl PhaseContext) {
+    let mut summoners = bus.subscribe(CURRENT_SUMMONER_URI);
+    let context = context.clone();
+
+    tauri::async_runtime::spawn(logging::inherit(async move {
+        follow_account(
+            context.app_config(),
+            context.account(),
+            &context,
+            &mut summoners,
+        )
+        .await;
+    }));
+}
+
+/// Reads the logged-in account after the client (re)connects. The client
+/// answers with an error until someone logs in; [`register`] picks the
+/// account up from then on.
+pub async fn switch_account(
+    lcu: &impl LcuApi,
+    config: &AppConfig,
+    account: &ActiveAccount,
+    emitter: &impl Emitter,
+) -> Result<()> {
+    let summoner = lcu.current_summoner().await?;
+    set_account(Some(summoner), config, account, emitter).await
+}
+
+async fn follow_account(
+    config: &AppConfig,
+    account: &ActiveAccount,
+    emitter: &impl Emitter,
+    summoners: &mut broadcast::Receiver<LcuEvent>,
+) {
+    while let Some(event) = event_bus::next(summoners).await {
+        let summoner = match event.event_type.as_str() {
+            "Delete" => None,
+            _ => match serde_json::from_value(event.data) {
+                Ok(summoner) => Some(summoner),
+                Err(error) => {
+                    log_warn!("Ignoring an unreadable current summoner update: {error}");
+                    continue;
+                }
+            },
+        };
+        if let Err(error) = set_account(summoner, config, account, emitter).await {
+            log_warn!("Failed to switch profiles: {error:#}");
+        }
+    }
+}
+
+/// Forgets the logged-in account once the client disconnects, so its profile
+/// does not outlive it.
+pub async fn forget_account(config: &AppConfig, account: &ActiveAccount, emitter: &impl Emitter) {
+    if let Err(error) = set_account(None, config, account, emitter).await {
+        log_warn!("Failed to clear the active profile: {error:#}");
+    }
+}
+
+/// Records the logged-in account, `None` when nobody is, and emits
+/// `active_profile_changed` if it is not the one seen last.
+async fn set_account(
+    summoner: Option<Summoner>,
+    config: &AppConfig,
+    account: &ActiveAccount,
+    emitter: &impl Emitter,
+) -> Result<()> {
+    {
+        let mut current = account.0.lock().await;
+        if *current == summoner {
+            return Ok(());
+        }
+        *current = summoner;
+    }
+
+    let active = active_profile(config, account).await;
+    match &active {
+        Some(ActiveProfile {
+            riot_id,
+            profile: Some(profile),
+        }) => log_info!("Using profile {profile} for {riot_id}"),
+        Some(ActiveProfile { riot_id, .. }) => {
+            log_info!("No profile for {riot_id}; using the default settings")
+        }
+        None => log_info!("No account is logged in; using the default settings"),
+    }
+    emitter.emit_event("active_profile_changed", active)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{Config, Profile},
+        emitter::RecordingEmitter,
+        lcu_api::fake::FakeLcuApi,
+    };
+
+    #[tokio::test]
+    async fn switches_profiles_when_the_account_changes() {
+        let lcu = FakeLcuApi::default();
+        let config = AppConfig::new(Config {
+            profiles: vec![Profile {
+                name: "Smurf".to_string(),
+                accounts: vec!["smurf-puuid".to_string()],
+                auto_accept: Some(true),
+                ..Profile::default()
+            }],
+            ..Config::default()
+        });
+        let account = ActiveAccount::default();
+        let emitter = RecordingEmitter::default();
+
+        switch_account(&lcu, &config, &account, &emitter)
+            .await
+            .unwrap();
+        switch_account(&lcu, &config, &account, &emitter)
+            .await
+            .unwrap();
+        lcu.state().summoner = Some(Summoner {
+            game_name: "Smurf".to_string(),
+            tag_line: "EUW".to_string(),
+            puuid: "smurf-puuid".to_string(),
+        });
+        switch_account(&lcu, &config, &account, &emitter)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            emitter.payloads("active_profile_changed"),
+            [
+                serde_json::json!({ "riotId": "Local Player#NA1", "profile": null }),
+                serde_json::json!({ "riotId": "Smurf#EUW", "profile": "Smurf" }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn follows_logins_and_forgets_the_account_on_disconnect() {
+        let config = AppConfig::new(Config::default());
+        let account = ActiveAccount::default();
+        let emitter = RecordingEmitter::default();
+        let bus = EventBus::default();
+        let mut summoners = bus.subscribe(CURRENT_SUMMONER_URI);
+
+        for data in [
+            serde_json::json!({ "message": "not logged in" }),
+            serde_json::json!({ "gameName": "Main", "tagLine": "NA1", "puuid": "main-puuid" }),
+            serde_json::json!({ "gameName": "Main", "tagLine": "NA1", "puuid": "main-puuid" }),
+        ] {
+            bus.publish(LcuEvent {
+                uri: CURRENT_SUMMONER_URI.to_string(),
+                event_type: "Update".to_string(),
+                data,
+            });
+        }
+        bus.close();
+        follow_account(&config, &account, &emitter, &mut summoners).await;
+        forget_account(&config, &account, &emitter).await;
+
+        assert_eq!(
+            emitter.payloads("active_profile_changed"),
+            [
+                serde_json::json!({ "riotId": "Main#NA1", "profile": null }),
+                serde_json::Value::Null,
+            ]
+        );
+        assert!(account.0.lock().await.is_none());
+    }
+}
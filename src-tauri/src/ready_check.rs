@@ -0,0 +1,424 @@
+use crate::{
+    config::{Config, ReadyCheckRules},
+    emitter::Emitter,
+    event_bus::{self, EventBus, PhaseReceiver},
+    gameflow::GameflowPhase,
+    lcu_api::LcuApi,
+    websocket::LcuEvent,
+};
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::{sync::broadcast, time::Instant};
+
+pub const READY_CHECK_URI: &str = "/lol-matchmaking/v1/ready-check";
+
+/// State of `/lol-matchmaking/v1/ready-check`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadyCheck {
+    /// `InProgress` while the check is waiting on players.
+    pub state: String,
+    pub player_response: PlayerResponse,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PlayerResponse {
+    None,
+    Accepted,
+    Declined,
+    #[serde(other)]
+    Other,
+}
+
+/// Outcome of an auto-accept attempt, sent to the frontend as
+/// `auto_accept_result`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", content = "reason", rename_all = "snake_case")]
+pub enum AutoAcceptResult {
+    Accepted,
+    Declined(String),
+    Skipped(String),
+    Failed(String),
+}
+
+/// What the ready-check rules decided to do.
+#[derive(Debug, PartialEq, Eq)]
+enum Decision {
+    Accept,
+    Decline(String),
+    /// Leave the ready check to the player.
+    Ignore(String),
+}
+
+/// Ready-check updates followed while auto-accept waits out its delay.
+pub struct ReadyCheckUpdates {
+    ready_checks: broadcast::Receiver<LcuEvent>,
+    phases: PhaseReceiver,
+}
+
+impl ReadyCheckUpdates {
+    pub fn subscribe(bus: &EventBus) -> Self {
+        Self {
+            ready_checks: bus.subscribe(READY_CHECK_URI),
+            phases: bus.subscribe_phases(&[GameflowPhase::ReadyCheck]),
+        }
+    }
+
+    /// Waits for the next ready-check state, or `None` once the ready check
+    /// is over.
+    async fn next(&mut self) -> Option<ReadyCheck> {
+        loop {
+            tokio::select! {
+                event = event_bus::next(&mut self.ready_checks) => {
+                    let event = event?;
+                    if event.event_type == "Delete" {
+                        return None;
+                    }
+                    match serde_json::from_value(event.data) {
+                        Ok(ready_check) => return Some(ready_check),
+                        Err(error) => log_warn!("Failed to parse ready check: {error}"),
+                    }
+                }
+                change = self.phases.next() => {
+                    if change?.left(&GameflowPhase::ReadyCheck) {
+                        return None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Applies the auto-accept setting and ready-check rules to a ready check,
+/// and reports the outcome to the frontend.
+pub async fn handle_ready_check(
+    lcu: &impl LcuApi,
+    emitter: &impl Emitter,
+    mut updates: ReadyCheckUpdates,
+    config: &Config,
+) {
+    let queue_id = match lcu.gameflow_session().await {
+        Ok(session) => Some(session.game_data.queue.id),
+        Err(error) => {
+            log_warn!("Could not read the ready check's queue: {error}");
+            None
+        }
+    };
+    let hour = chrono::Local::now().hour();
+    log_debug!(
+        "Evaluating the ready check for queue {queue_id:?} at hour {hour}; auto_accept={}",
+        config.auto_accept
+    );
+
+    let result = match evaluate(
+        &config.ready_check_rules,
+        config.auto_accept,
+        queue_id,
+        hour,
+    ) {
+        Decision::Accept => {
+            log_info!("Ready check for queue {queue_id:?} will be accepted automatically");
+            auto_accept(lcu, &mut updates, config.accept_delay).await
+        }
+        Decision::Decline(reason) => {
+            log_info!("Declining ready check for queue {queue_id:?}: {reason}");
+            match lcu.decline_ready_check().await {
+                Ok(()) => AutoAcceptResult::Declined(reason),
+                Err(error) => AutoAcceptResult::Failed(error.to_string()),
+            }
+        }
+        Decision::Ignore(reason) => {
+            log_info!("Leaving ready check for queue {queue_id:?} to the player: {reason}");
+            if !config.auto_accept {
+                return;
+            }
+            AutoAcceptResult::Skipped(reason)
+        }
+    };
+
+    match &result {
+        AutoAcceptResult::Accepted => log_info!("Ready check accepted automatically"),
+        AutoAcceptResult::Declined(_) => log_info!("Ready check declined automatically"),
+        AutoAcceptResult::Skipped(reason) => log_info!("Ready-check auto-accept skipped: {reason}"),
+        AutoAcceptResult::Failed(error) => log_error!("Ready-check automation failed: {error}"),
+    }
+
+    if let Err(error) = emitter.emit_event("auto_accept_result", result) {
+        log_error!("Failed to emit auto-accept result: {error}");
+    }
+}
+
+/// Decides what to do with a ready check for `queue_id` popping at the
+/// local `hour`. Declining takes precedence over accepting.
+fn evaluate(
+    rules: &ReadyCheckRules,
+    auto_accept: bool,
+    queue_id: Option<i64>,
+    hour: u32,
+) -> Decision {
+    if rules.do_not_disturb {
+        return Decision::Decline("do not disturb is on".to_string());
+    }
+    if let Some(hours) = rules.decline_hours.filter(|hours| hours.contains(hour)) {
+        return Decision::Decline(format!(
+            "ready checks are declined from {}:00 to {}:00",
+            hours.start, hours.end
+        ));
+    }
+    if !auto_accept {
+        return Decision::Ignore("auto-accept is off".to_string());
+    }
+
+    match queue_id {
+        Some(queue_id) if rules.blacklisted_queue_ids.contains(&queue_id) => {
+            Decision::Ignore(format!("queue {queue_id} is blacklisted"))
+        }
+        Some(queue_id)
+            if !rules.accept_queue_ids.is_empty()
+                && !rules.accept_queue_ids.contains(&queue_id) =>
+        {
+            Decision::Ignore(format!("queue {queue_id} is not in the accepted queues"))
+        }
+        None if !rules.accept_queue_ids.is_empty() || !rules.blacklisted_queue_ids.is_empty() => {
+            Decision::Ignore("the queue could not be determined".to_string())
+        }
+        _ => Decision::Accept,
+    }
+}
+
+async fn auto_accept(
+    lcu: &impl LcuApi,
+    updates: &mut ReadyCheckUpdates,
+    accept_delay: u32,
+) -> AutoAcceptResult {
+    // The delay counts from the ready check popping, which is about a
+    // second before Reveal sees it.
+    let wait = u64::from(accept_delay).saturating_sub(1_000);
+    let accept_at = Instant::now() + Duration::from_millis(wait);
+    log_debug!("Accepting the ready check in {wait} ms unless it is answered first");
+    let mut ready_check = match lcu.ready_check().await {
+        Ok(ready_check) => Some(ready_check),
+        Err(error) => {
+            log_debug!("Could not read the ready check yet; waiting for updates: {error}");
+            None
+        }
+    };
+
+    loop {
+        if let Some(reason) = ready_check.as_ref().and_then(skip_reason) {
+            return AutoAcceptResult::Skipped(reason);
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep_until(accept_at) => {
+                log_debug!("Accept delay elapsed; accepting the ready check");
+                break;
+            }
+            update = updates.next() => match update {
+                Some(update) => {
+                    log_trace!(
+                        "Ready check update: state={}, player_response={:?}",
+                        update.state,
+                        update.player_response
+                    );
+                    ready_check = Some(update);
+                }
+                None => return AutoAcceptResult::Skipped("the ready check ended".to_string()),
+            },
+        }
+    }
+
+    match lcu.accept_ready_check().await {
+        Ok(()) => AutoAcceptResult::Accepted,
+        Err(error) => AutoAcceptResult::Failed(error.to_string()),
+    }
+}
+
+/// Why the ready check should not be accepted, if it should not.
+fn skip_reason(ready_check: &ReadyCheck) -> Option<String> {
+    let reason = match ready_check.player_response {
+        PlayerResponse::Accepted => "the ready check was already accepted",
+        PlayerResponse::Declined => "the ready check was declined",
+        _ if ready_check.state != "InProgress" => "the ready check ended",
+        _ => return None,
+    };
+
+    Some(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::HourRange, emitter::RecordingEmitter, lcu_api::fake::FakeLcuApi};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn ready_check(player_response: &str) -> serde_json::Value {
+        json!({
+            "declinerIds": [],
+            "dodgeWarning": "None",
+            "playerResponse": player_response,
+            "state": "InProgress",
+            "suppressUx": false,
+            "timer": 2.0
+        })
+    }
+
+    fn fake_ready_check() -> FakeLcuApi {
+        let lcu = FakeLcuApi::default();
+        let mut state = lcu.state();
+        state.ready_check = Some(serde_json::from_value(ready_check("None")).unwrap());
+        state.queue_id = Some(420);
+        drop(state);
+        lcu
+    }
+
+    fn auto_accept_config(accept_delay: u32) -> Config {
+        Config {
+            auto_accept: true,
+            accept_delay,
+            ..Config::default()
+        }
+    }
+
+    fn spawn_ready_check(
+        lcu: &FakeLcuApi,
+        config: Config,
+    ) -> (
+        Arc<EventBus>,
+        Arc<RecordingEmitter>,
+        tokio::task::JoinHandle<()>,
+    ) {
+        let bus = Arc::new(EventBus::default());
+        bus.publish_phase(GameflowPhase::ReadyCheck);
+        let emitter = Arc::new(RecordingEmitter::default());
+        let updates = ReadyCheckUpdates::subscribe(&bus);
+        let task = tokio::spawn({
+            let lcu = lcu.clone();
+            let emitter = emitter.clone();
+            async move { handle_ready_check(&lcu, emitter.as_ref(), updates, &config).await }
+        });
+        (bus, emitter, task)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn accepts_the_ready_check_after_the_configured_delay() {
+        let lcu = fake_ready_check();
+        let (_bus, emitter, task) = spawn_ready_check(&lcu, auto_accept_config(3_000));
+
+        tokio::time::sleep(Duration::from_millis(1_990)).await;
+        assert!(lcu.actions().is_empty());
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(lcu.actions(), ["accept_ready_check"]);
+        task.await.unwrap();
+        assert_eq!(
+            emitter.payloads("auto_accept_result"),
+            [json!({ "outcome": "accepted" })]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn skips_a_ready_check_declined_during_the_delay() {
+        let lcu = fake_ready_check();
+        let (bus, emitter, task) = spawn_ready_check(&lcu, auto_accept_config(3_000));
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        bus.publish(LcuEvent {
+            uri: READY_CHECK_URI.to_string(),
+            event_type: "Update".to_string(),
+            data: ready_check("Declined"),
+        });
+        task.await.unwrap();
+
+        assert!(lcu.actions().is_empty());
+        assert_eq!(
+            emitter.payloads("auto_accept_result"),
+            [json!({ "outcome": "skipped", "reason": "the ready check was declined" })]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn skips_a_ready_check_that_was_already_answered() {
+        let lcu = fake_ready_check();
+        lcu.state().ready_check = Some(serde_json::from_value(ready_check("Accepted")).unwrap());
+
+        let (_bus, emitter, task) = spawn_ready_check(&lcu, auto_accept_config(3_000));
+        task.await.unwrap();
+
+        assert!(lcu.actions().is_empty());
+        assert_eq!(
+            emitter.payloads("auto_accept_result")[0]["outcome"],
+            "skipped"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn skips_when_the_phase_leaves_ready_check() {
+        let lcu = fake_ready_check();
+        let (bus, emitter, task) = spawn_ready_check(&lcu, auto_accept_config(3_000));
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        bus.publish_phase(GameflowPhase::Matchmaking);
+        task.await.unwrap();
+
+        assert!(lcu.actions().is_empty());
+        assert_eq!(
+            emitter.payloads("auto_accept_result"),
+            [json!({ "outcome": "skipped", "reason": "the ready check ended" })]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn declines_while_do_not_disturb_is_on() {
+        let lcu = fake_ready_check();
+        let mut config = auto_accept_config(3_000);
+        config.ready_check_rules.do_not_disturb = true;
+
+        let (_bus, emitter, task) = spawn_ready_check(&lcu, config);
+        task.await.unwrap();
+
+        assert_eq!(lcu.actions(), ["decline_ready_check"]);
+        assert_eq!(
+            emitter.payloads("auto_accept_result"),
+            [json!({ "outcome": "declined", "reason": "do not disturb is on" })]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stays_silent_when_no_automation_applies() {
+        let lcu = fake_ready_check();
+
+        let (_bus, emitter, task) = spawn_ready_check(&lcu, Config::default());
+        task.await.unwrap();
+
+        assert!(lcu.actions().is_empty());
+        assert!(emitter.payloads("auto_accept_result").is_empty());
+    }
+
+    #[test]
+    fn evaluates_ready_check_rules() {
+        let rules = ReadyCheckRules {
+            accept_queue_ids: vec![420, 440],
+            blacklisted_queue_ids: vec![440],
+            do_not_disturb: false,
+            decline_hours: Some(HourRange { start: 23, end: 7 }),
+        };
+        let ignored = |decision: Decision| matches!(decision, Decision::Ignore(_));
+
+        assert_eq!(evaluate(&rules, true, Some(420), 12), Decision::Accept);
+        assert!(ignored(evaluate(&rules, true, Some(440), 12)));
+        assert!(ignored(evaluate(&rules, true, Some(450), 12)));
+        assert!(ignored(evaluate(&rules, true, None, 12)));
+        assert!(ignored(evaluate(&rules, false, Some(420), 12)));
+        assert!(matches!(
+            evaluate(&rules, false, Some(420), 2),
+            Decision::Decline(_)
+        ));
+        assert_eq!(
+            evaluate(&ReadyCheckRules::default(), true, None, 2),
+            Decision::Accept
+        );
+    }
+}
@@ -1,7 +1,249 @@
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RegionInfo {
     pub web_region: String,
 }
+
+/// A League server, with the names other services use for it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Region {
+    Na,
+    Euw,
+    Eune,
+    Oce,
+    Tr,
+    Ru,
+    Jp,
+    Kr,
+    Br,
+    Lan,
+    Las,
+    Ph,
+    Sg,
+    Th,
+    Tw,
+    Vn,
+    Me,
+    Pbe,
+    /// A server Reveal does not know, by the client's web region, which
+    /// stands in for all of its other names.
+    Other(String),
+}
+
+/// The regional routing value Riot's APIs group platforms by.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RoutingCluster {
+    Americas,
+    Europe,
+    Asia,
+    Sea,
+}
+
+impl RoutingCluster {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RoutingCluster::Americas => "americas",
+            RoutingCluster::Europe => "europe",
+            RoutingCluster::Asia => "asia",
+            RoutingCluster::Sea => "sea",
+        }
+    }
+}
+
+impl Region {
+    pub const ALL: [Region; 18] = [
+        Region::Na,
+        Region::Euw,
+        Region::Eune,
+        Region::Oce,
+        Region::Tr,
+        Region::Ru,
+        Region::Jp,
+        Region::Kr,
+        Region::Br,
+        Region::Lan,
+        Region::Las,
+        Region::Ph,
+        Region::Sg,
+        Region::Th,
+        Region::Tw,
+        Region::Vn,
+        Region::Me,
+        Region::Pbe,
+    ];
+
+    /// `(name, platform ID, routing cluster)`.
+    fn data(&self) -> (&str, &str, Option<RoutingCluster>) {
+        use RoutingCluster::*;
+        match self {
+            Region::Na => ("NA", "NA1", Some(Americas)),
+            Region::Euw => ("EUW", "EUW1", Some(Europe)),
+            Region::Eune => ("EUNE", "EUN1", Some(Europe)),
+            Region::Oce => ("OCE", "OC1", Some(Sea)),
+            Region::Tr => ("TR", "TR1", Some(Europe)),
+            Region::Ru => ("RU", "RU", Some(Europe)),
+            Region::Jp => ("JP", "JP1", Some(Asia)),
+            Region::Kr => ("KR", "KR", Some(Asia)),
+            Region::Br => ("BR", "BR1", Some(Americas)),
+            Region::Lan => ("LAN", "LA1", Some(Americas)),
+            Region::Las => ("LAS", "LA2", Some(Americas)),
+            Region::Ph => ("PH", "PH2", Some(Sea)),
+            Region::Sg => ("SG", "SG2", Some(Sea)),
+            Region::Th => ("TH", "TH2", Some(Sea)),
+            Region::Tw => ("TW", "TW2", Some(Sea)),
+            Region::Vn => ("VN", "VN2", Some(Sea)),
+            Region::Me => ("ME", "ME1", Some(Europe)),
+            Region::Pbe => ("PBE", "PBE1", Some(Americas)),
+            Region::Other(web_region) => (web_region, web_region, None),
+        }
+    }
+
+    /// The short name players know the server by, e.g. `EUNE`.
+    pub fn name(&self) -> &str {
+        self.data().0
+    }
+
+    /// Riot's platform ID, e.g. `EUN1`.
+    pub fn platform_id(&self) -> &str {
+        self.data().1
+    }
+
+    /// `None` for [`Region::Other`].
+    pub fn routing(&self) -> Option<RoutingCluster> {
+        self.data().2
+    }
+
+    /// How the multi-search provider `provider` names this server, or `None`
+    /// if it has no pages for it. Custom providers get the short name.
+    pub fn slug(&self, provider: &str) -> Option<String> {
+        match (self, provider) {
+            (Region::Other(web_region), _) => Some(web_region.clone()),
+            // None of the built-in sites track the test server.
+            (Region::Pbe, "opgg" | "deeplol" | "ugg" | "tracker" | "fow") => None,
+            (_, "ugg") => Some(self.platform_id().to_lowercase()),
+            (_, "fow") => Some(self.name().to_lowercase()),
+            _ => Some(self.name().to_string()),
+        }
+    }
+
+    /// Accepts short names and platform IDs in any case, e.g. `sg2` or `SG`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        Self::ALL.into_iter().find(|region| {
+            region.name().eq_ignore_ascii_case(value)
+                || region.platform_id().eq_ignore_ascii_case(value)
+        })
+    }
+
+    /// Like [`Region::parse`], but keeps unknown values as [`Region::Other`]
+    /// so new servers still get links. Fails only for an empty value.
+    pub fn parse_or_other(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if value.is_empty() {
+            bail!("the League region is empty");
+        }
+        Ok(Self::parse(value).unwrap_or_else(|| Region::Other(value.to_string())))
+    }
+}
+
+impl TryFrom<&RegionInfo> for Region {
+    type Error = anyhow::Error;
+
+    fn try_from(info: &RegionInfo) -> Result<Self> {
+        Region::parse_or_other(&info.web_region)
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knows_every_platform_and_routing_cluster() {
+        use RoutingCluster::*;
+        let table = [
+            (Region::Na, "NA1", Americas),
+            (Region::Euw, "EUW1", Europe),
+            (Region::Eune, "EUN1", Europe),
+            (Region::Oce, "OC1", Sea),
+            (Region::Tr, "TR1", Europe),
+            (Region::Ru, "RU", Europe),
+            (Region::Jp, "JP1", Asia),
+            (Region::Kr, "KR", Asia),
+            (Region::Br, "BR1", Americas),
+            (Region::Lan, "LA1", Americas),
+            (Region::Las, "LA2", Americas),
+            (Region::Ph, "PH2", Sea),
+            (Region::Sg, "SG2", Sea),
+            (Region::Th, "TH2", Sea),
+            (Region::Tw, "TW2", Sea),
+            (Region::Vn, "VN2", Sea),
+            (Region::Me, "ME1", Europe),
+            (Region::Pbe, "PBE1", Americas),
+        ];
+        assert_eq!(table.len(), Region::ALL.len());
+
+        for (region, platform_id, routing) in table {
+            assert_eq!(region.platform_id(), platform_id, "{region}");
+            assert_eq!(region.routing(), Some(routing), "{region}");
+            assert_eq!(Region::parse(platform_id).as_ref(), Some(&region));
+            assert_eq!(
+                Region::parse(&platform_id.to_lowercase()).as_ref(),
+                Some(&region)
+            );
+            assert_eq!(Region::parse(region.name()).as_ref(), Some(&region));
+        }
+    }
+
+    #[test]
+    fn uses_each_sites_own_region_names() {
+        // U.GG goes by platform ID, with no number for Korea and Russia.
+        assert_eq!(Region::Eune.slug("ugg").as_deref(), Some("eun1"));
+        assert_eq!(Region::Kr.slug("ugg").as_deref(), Some("kr"));
+        assert_eq!(Region::Lan.slug("ugg").as_deref(), Some("la1"));
+        // FOW.LOL wants short names in lowercase.
+        assert_eq!(Region::Eune.slug("fow").as_deref(), Some("eune"));
+        // The others use short names as shown in the client.
+        for provider in ["opgg", "deeplol", "tracker", "custom"] {
+            assert_eq!(Region::Oce.slug(provider).as_deref(), Some("OCE"));
+        }
+
+        for provider in ["opgg", "deeplol", "ugg", "tracker", "fow"] {
+            assert_eq!(Region::Pbe.slug(provider), None, "{provider}");
+        }
+        assert_eq!(Region::Pbe.slug("custom").as_deref(), Some("PBE"));
+
+        let other = Region::Other("NEW1".to_string());
+        for provider in ["opgg", "deeplol", "ugg", "tracker", "fow", "custom"] {
+            assert_eq!(other.slug(provider).as_deref(), Some("NEW1"));
+        }
+    }
+
+    #[test]
+    fn builds_regions_from_the_client_region_info() {
+        let region = |web_region: &str| {
+            Region::try_from(&RegionInfo {
+                web_region: web_region.to_string(),
+            })
+        };
+
+        assert_eq!(region("SG2").unwrap(), Region::Sg);
+        assert_eq!(region("na").unwrap(), Region::Na);
+        assert_eq!(
+            region(" atlantis ").unwrap(),
+            Region::Other("atlantis".to_string())
+        );
+        assert_eq!(region("atlantis").unwrap().platform_id(), "atlantis");
+        assert!(region("").is_err());
+    }
+}
@@ -0,0 +1,319 @@
+use crate::{
+    champ_select::{self, ChampSelectSession, ChampSelectTeamMember, SessionEvent},
+    emitter::Emitter,
+    lcu_api::LcuApi,
+    match_history::MatchHistory,
+    websocket::LcuEvent,
+};
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::broadcast;
+
+/// Fewer recent games than this say too little about a player to flag them.
+const MIN_GAMES: usize = 5;
+/// A position played in less than this share of recent games is off-role.
+const OFF_ROLE_PERCENT: usize = 10;
+
+/// Why a teammate may be playing outside their comfort zone.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RoleFlag {
+    /// Rarely plays the assigned position, e.g. after being autofilled.
+    OffRole {
+        position: String,
+        /// Recent games in that position.
+        position_games: usize,
+        /// Recent games with a known position.
+        games: usize,
+    },
+    /// Has not played the selected champion in any recent game.
+    FirstTimeChampion { champion_id: u32, games: usize },
+}
+
+/// Flags a teammate from their Champ Select slot and recent games.
+pub fn role_flags(
+    puuid: &str,
+    member: &ChampSelectTeamMember,
+    history: &MatchHistory,
+) -> Vec<RoleFlag> {
+    let played: Vec<_> = history
+        .games
+        .games
+        .iter()
+        .filter_map(|game| game.player(puuid))
+        .collect();
+    let mut flags = Vec::new();
+
+    if !member.assigned_position.is_empty() {
+        let positions: Vec<_> = played
+            .iter()
+            .filter_map(|player| player.position())
+            .collect();
+        let position_games = positions
+            .iter()
+            .filter(|position| position.eq_ignore_ascii_case(&member.assigned_position))
+            .count();
+        if positions.len() >= MIN_GAMES && position_games * 100 < positions.len() * OFF_ROLE_PERCENT
+        {
+            flags.push(RoleFlag::OffRole {
+                position: member.assigned_position.to_lowercase(),
+                position_games,
+                games: positions.len(),
+            });
+        }
+    }
+
+    let champion_id = match member.champion_id {
+        0 => member.champion_pick_intent,
+        champion_id => champion_id,
+    };
+    if champion_id != 0
+        && played.len() >= MIN_GAMES
+        && !played
+            .iter()
+            .any(|player| player.champion_id == champion_id)
+    {
+        flags.push(RoleFlag::FirstTimeChampion {
+            champion_id,
+            games: played.len(),
+        });
+    }
+
+    flags
+}
+
+/// Flagged teammates by PUUID, the payload of `role_flags_updated`.
+pub type TeamRoleFlags = BTreeMap<String, Vec<RoleFlag>>;
+
+/// Flags teammates from their recent games and re-flags them whenever their
+/// position or champion changes. Runs beside the reveal so slow match
+/// history lookups never hold it up, and stops when the session stream ends.
+pub async fn run(
+    lcu: &impl LcuApi,
+    emitter: &impl Emitter,
+    sessions: &mut broadcast::Receiver<LcuEvent>,
+) {
+    let mut checks = RoleChecks::default();
+    let mut session = lcu.champ_select_session().await.ok();
+
+    loop {
+        let flags = match &session {
+            Some(session) => checks.update(lcu, session).await,
+            None => None,
+        };
+        if let Some(flags) = flags {
+            log_debug!("Role flags changed for {} teammates", flags.len());
+            if let Err(error) = emitter.emit_event("role_flags_updated", flags) {
+                log_warn!("Failed to send role flags to the frontend: {error}");
+            }
+        }
+
+        let Some(SessionEvent::Update(update)) = champ_select::next_session(sessions).await else {
+            return;
+        };
+        session = Some(*update);
+    }
+}
+
+/// Recent games of the teammates seen during one Champ Select, each looked
+/// up once.
+#[derive(Default)]
+struct RoleChecks {
+    /// `None` when the lookup failed.
+    histories: HashMap<String, Option<MatchHistory>>,
+    last_flags: TeamRoleFlags,
+}
+
+impl RoleChecks {
+    /// Flags the teammates in `session`, returning the flags if they differ
+    /// from the last ones returned.
+    async fn update(
+        &mut self,
+        lcu: &impl LcuApi,
+        session: &ChampSelectSession,
+    ) -> Option<TeamRoleFlags> {
+        let members: Vec<&ChampSelectTeamMember> = session
+            .my_team
+            .iter()
+            .filter(|member| !member.puuid.is_empty())
+            .collect();
+
+        let lookups = members
+            .iter()
+            .map(|member| member.puuid.as_str())
+            .filter(|puuid| !self.histories.contains_key(*puuid))
+            .map(|puuid| async move {
+                let history = lcu
+                    .match_history(puuid)
+                    .await
+                    .map_err(|error| {
+                        log_warn!("Match history is not available for a teammate: {error}")
+                    })
+                    .ok();
+                (puuid.to_string(), history)
+            });
+        self.histories.extend(join_all(lookups).await);
+
+        let flags: TeamRoleFlags = members
+            .into_iter()
+            .filter_map(|member| {
+                let history = self.histories.get(&member.puuid)?.as_ref()?;
+                let flags = role_flags(&member.puuid, member, history);
+                (!flags.is_empty()).then(|| (member.puuid.clone(), flags))
+            })
+            .collect();
+        if flags == self.last_flags {
+            return None;
+        }
+        self.last_flags = flags.clone();
+        Some(flags)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        champ_select::SESSION_URI, emitter::RecordingEmitter, event_bus::EventBus,
+        lcu_api::fake::FakeLcuApi,
+    };
+    use std::sync::Arc;
+
+    fn history(fixture: &str) -> MatchHistory {
+        serde_json::from_str(fixture).unwrap()
+    }
+
+    fn jungle_main() -> MatchHistory {
+        history(include_str!("../fixtures/match-history/jungle_main.json"))
+    }
+
+    fn member(
+        position: &str,
+        champion_id: u32,
+        champion_pick_intent: u32,
+    ) -> ChampSelectTeamMember {
+        ChampSelectTeamMember {
+            assigned_position: position.to_string(),
+            puuid: "jungle-puuid".to_string(),
+            champion_id,
+            champion_pick_intent,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn flags_an_autofilled_support() {
+        let history = history(include_str!(
+            "../fixtures/match-history/autofilled_support.json"
+        ));
+
+        let flags = role_flags(
+            "support-puuid",
+            &ChampSelectTeamMember {
+                puuid: "support-puuid".to_string(),
+                ..member("utility", 0, 0)
+            },
+            &history,
+        );
+
+        assert_eq!(
+            flags,
+            [RoleFlag::OffRole {
+                position: "utility".to_string(),
+                position_games: 0,
+                games: 20,
+            }]
+        );
+    }
+
+    #[test]
+    fn flags_a_champion_missing_from_recent_games() {
+        let history = jungle_main();
+
+        assert_eq!(
+            role_flags("jungle-puuid", &member("jungle", 64, 0), &history),
+            []
+        );
+        assert_eq!(
+            role_flags("jungle-puuid", &member("jungle", 0, 99), &history),
+            [RoleFlag::FirstTimeChampion {
+                champion_id: 99,
+                games: 8,
+            }]
+        );
+        // One game in eight is enough to not count as off-role.
+        assert_eq!(
+            role_flags("jungle-puuid", &member("middle", 0, 0), &history),
+            []
+        );
+        assert_eq!(
+            role_flags("jungle-puuid", &member("top", 0, 0), &history),
+            [RoleFlag::OffRole {
+                position: "top".to_string(),
+                position_games: 0,
+                games: 8,
+            }]
+        );
+    }
+
+    #[test]
+    fn does_not_flag_players_with_few_recent_games() {
+        let mut history = jungle_main();
+        history.games.games.truncate(MIN_GAMES - 1);
+
+        assert_eq!(
+            role_flags("jungle-puuid", &member("top", 99, 0), &history),
+            []
+        );
+    }
+
+    #[tokio::test]
+    async fn reflags_teammates_when_their_champion_changes() {
+        let lcu = FakeLcuApi::default();
+        lcu.state()
+            .match_histories
+            .insert("jungle-puuid".to_string(), jungle_main());
+        let session = |champion_pick_intent: u32| {
+            serde_json::json!({
+                "gameId": 1,
+                "timer": { "adjustedTimeLeftInPhase": 30_000, "phase": "PLANNING" },
+                "myTeam": [
+                    {
+                        "cellId": 0,
+                        "assignedPosition": "jungle",
+                        "puuid": "jungle-puuid",
+                        "championPickIntent": champion_pick_intent
+                    },
+                    { "cellId": 1, "assignedPosition": "top", "puuid": "unknown-puuid" }
+                ]
+            })
+        };
+        lcu.state().session = Some(serde_json::from_value(session(64)).unwrap());
+        let bus = Arc::new(EventBus::default());
+        let mut sessions = bus.subscribe(SESSION_URI);
+        let emitter = RecordingEmitter::default();
+
+        for champion_pick_intent in [64, 99, 99, 64] {
+            bus.publish(LcuEvent {
+                uri: SESSION_URI.to_string(),
+                event_type: "Update".to_string(),
+                data: session(champion_pick_intent),
+            });
+        }
+        bus.close();
+        run(&lcu, &emitter, &mut sessions).await;
+
+        assert_eq!(
+            emitter.payloads("role_flags_updated"),
+            [
+                serde_json::json!({
+                    "jungle-puuid": [
+                        { "kind": "first_time_champion", "champion_id": 99, "games": 8 }
+                    ]
+                }),
+                serde_json::json!({}),
+            ]
+        );
+    }
+}
@@ -1,71 +1,418 @@
-use crate::{champ_select::handle_champ_select_start, config::AppConfig};
-use shaco::rest::RESTClient;
+use crate::{
+    app_state::Dodge,
+    auto_draft,
+    champ_select::{handle_champ_select_start, ChampSelectUpdates, PlayerRecords, SESSION_URI},
+    config::{self, ActiveAccount, AppConfig, Config},
+    emitter::Emitter,
+    end_of_game,
+    event_bus::EventBus,
+    gameflow::GameflowPhase,
+    lcu_api::LcuApi,
+    live_client::{self, InGame},
+    lobby_history::LobbyHistory,
+    logging,
+    player_notes::PlayerNotes,
+    presets::AppPresets,
+    ready_check::{handle_ready_check, ReadyCheckUpdates, READY_CHECK_URI},
+    role_check,
+    supervisor::PhaseSupervisor,
+};
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 use tauri::{AppHandle, Manager};
 
-pub async fn get_gameflow_state(remoting_client: &RESTClient) -> anyhow::Result<String> {
-    let gameflow_state = remoting_client
-        .get("/lol-gameflow/v1/gameflow-phase".to_string())
-        .await?;
+/// What the features need from the app. Implemented for [`AppHandle`];
+/// tests use [`fake::TestContext`].
+pub trait PhaseContext: Emitter + Clone + 'static {
+    fn supervisor(&self) -> &PhaseSupervisor;
+    /// The config as saved, without any profile applied.
+    fn app_config(&self) -> &AppConfig;
+    fn account(&self) -> &ActiveAccount;
+    fn records(&self) -> PlayerRecords;
+    fn in_game(&self) -> &InGame;
+    fn dodge(&self) -> &Dodge;
+    fn presets(&self) -> &AppPresets;
 
-    Ok(gameflow_state.to_string().replace('\"', ""))
+    /// The config with the logged-in account's profile applied.
+    fn config(&self) -> impl Future<Output = Config> + Send {
+        config::effective_for(self.app_config(), self.account())
+    }
+}
+
+impl PhaseContext for AppHandle {
+    fn supervisor(&self) -> &PhaseSupervisor {
+        self.state::<PhaseSupervisor>().inner()
+    }
+
+    fn app_config(&self) -> &AppConfig {
+        self.state::<AppConfig>().inner()
+    }
+
+    fn account(&self) -> &ActiveAccount {
+        self.state::<ActiveAccount>().inner()
+    }
+
+    fn records(&self) -> PlayerRecords {
+        PlayerRecords {
+            history: self.state::<LobbyHistory>().inner().clone(),
+            notes: self.state::<PlayerNotes>().inner().clone(),
+        }
+    }
+
+    fn in_game(&self) -> &InGame {
+        self.state::<InGame>().inner()
+    }
+
+    fn dodge(&self) -> &Dodge {
+        self.state::<Dodge>().inner()
+    }
+
+    fn presets(&self) -> &AppPresets {
+        self.state::<AppPresets>().inner()
+    }
+}
+
+/// Reacts to gameflow phase changes until the bus closes. Tasks started for a
+/// phase are cancelled when it ends, and all of them when the client
+/// disconnects.
+pub fn register(bus: &Arc<EventBus>, context: &impl PhaseContext, lcu: &impl LcuApi) {
+    let mut phases = bus.subscribe_phases(&[]);
+    bus.register_uri(READY_CHECK_URI);
+    let bus = bus.clone();
+    let context = context.clone();
+    let lcu = lcu.clone();
+
+    tauri::async_runtime::spawn(logging::inherit(async move {
+        while let Some(change) = phases.next().await {
+            // Cancel before handling the new phase so a quick re-entry keeps
+            // the task it starts.
+            context.supervisor().cancel_phase(&change.previous);
+            if change.left(&GameflowPhase::InProgress) {
+                context.in_game().clear().await;
+            }
+            handle_client_state(change.current, &bus, &context, &lcu).await;
+        }
+        context.supervisor().cancel_all();
+        context.in_game().clear().await;
+    }));
 }
 
-pub async fn handle_client_state(
-    client_state: String,
-    app_handle: &AppHandle,
-    remoting_client: &RESTClient,
-    app_client: &RESTClient,
+/// `game-<id>` for the current Champ Select, so its lines can be told apart
+/// from the previous game's. Keeps the connection's correlation when the
+/// session cannot be read.
+async fn game_correlation(lcu: &impl LcuApi) -> String {
+    match lcu.champ_select_session().await {
+        Ok(session) => format!("game-{}", session.game_id),
+        Err(error) => {
+            log_warn!("Failed to read the Champ Select game ID: {error}");
+            logging::correlation().unwrap_or_else(|| "game-unknown".to_string())
+        }
+    }
+}
+
+/// The game's correlation, read once per Champ Select and shared by its
+/// tasks.
+type SharedCorrelation = Shared<BoxFuture<'static, String>>;
+
+/// Runs a Champ Select task with the game's correlation. The task waits for
+/// it, so the phase loop never waits on the session.
+async fn champ_select_scope<F: Future>(game: SharedCorrelation, task: F) -> F::Output {
+    logging::scoped(game.await, task).await
+}
+
+/// `ready-check-<n>`, counting ready checks since Reveal started, so the
+/// lines of a ready check that pops again can be told apart.
+fn ready_check_correlation() -> String {
+    static READY_CHECKS: AtomicUsize = AtomicUsize::new(0);
+    format!(
+        "ready-check-{}",
+        READY_CHECKS.fetch_add(1, Ordering::Relaxed) + 1
+    )
+}
+
+async fn handle_client_state(
+    client_state: GameflowPhase,
+    bus: &EventBus,
+    context: &impl PhaseContext,
+    lcu: &impl LcuApi,
 ) {
     log_info!("League Client state changed to {client_state}");
-    match client_state.as_str() {
-        "ChampSelect" => {
-            let cloned_app_handle = app_handle.clone();
-            let cloned_app_client = app_client.clone();
-            let cloned_remoting = remoting_client.clone();
+    match client_state {
+        GameflowPhase::ChampSelect => {
+            let updates = ChampSelectUpdates::subscribe(bus);
+            let cloned_context = context.clone();
+            let cloned_lcu = lcu.clone();
+            let records = context.records();
+            let game = {
+                let lcu = lcu.clone();
+                logging::inherit(async move { game_correlation(&lcu).await })
+                    .boxed()
+                    .shared()
+            };
 
             // clone config and pass to async task
-            let cfg = app_handle.state::<AppConfig>();
-            let cfg = cfg.0.lock().await.clone();
-
-            tauri::async_runtime::spawn(async move {
-                handle_champ_select_start(
-                    &cloned_app_client,
-                    &cloned_remoting,
-                    &cfg,
-                    &cloned_app_handle,
-                )
-                .await;
-            });
-        }
-        "ReadyCheck" => {
-            let cfg = {
-                let cfg = app_handle.state::<AppConfig>();
-                let value = cfg.0.lock().await.clone();
-                value
-            };
-            if cfg.auto_accept {
-                log_info!("Auto-accept is enabled; scheduling ready-check acceptance");
-                tokio::time::sleep(std::time::Duration::from_millis(
-                    u64::from(cfg.accept_delay).saturating_sub(1_000),
-                ))
-                .await;
-                if let Err(error) = remoting_client
-                    .post(
-                        "/lol-matchmaking/v1/ready-check/accept".to_string(),
-                        serde_json::json!({}),
-                    )
-                    .await
-                {
-                    log_error!("Ready-check auto-accept failed: {error}");
-                } else {
-                    log_info!("Ready check accepted automatically");
-                }
+            let cfg = context.config().await;
+
+            if cfg.auto_draft.auto_ban || cfg.auto_draft.auto_pick {
+                let mut sessions = bus.subscribe(SESSION_URI);
+                let cloned_lcu = lcu.clone();
+                let draft = cfg.auto_draft.clone();
+                let game = game.clone();
+                context
+                    .supervisor()
+                    .start(GameflowPhase::ChampSelect, "auto_draft", async move {
+                        let draft = auto_draft::run(&cloned_lcu, &draft, &mut sessions);
+                        champ_select_scope(game, draft).await
+                    });
             }
+
+            let mut sessions = bus.subscribe(SESSION_URI);
+            let role_check_lcu = lcu.clone();
+            let role_check_context = context.clone();
+            let role_check_game = game.clone();
+            context
+                .supervisor()
+                .start(GameflowPhase::ChampSelect, "role_checks", async move {
+                    let checks =
+                        role_check::run(&role_check_lcu, &role_check_context, &mut sessions);
+                    champ_select_scope(role_check_game, checks).await
+                });
+
+            context.supervisor().start(
+                GameflowPhase::ChampSelect,
+                "champ_select_tracker",
+                async move {
+                    let tracker = handle_champ_select_start(
+                        &cloned_lcu,
+                        &cfg,
+                        &cloned_context,
+                        &records,
+                        updates,
+                    );
+                    champ_select_scope(game, tracker).await
+                },
+            );
+        }
+        GameflowPhase::ReadyCheck => {
+            let cfg = context.config().await;
+            let updates = ReadyCheckUpdates::subscribe(bus);
+            let cloned_context = context.clone();
+            let cloned_lcu = lcu.clone();
+            // The task stops by itself when the ready check ends, after
+            // reporting how it ended, so it is not cancelled with the phase.
+            context.supervisor().start_until_done(
+                GameflowPhase::ReadyCheck,
+                "ready_check",
+                logging::scoped(ready_check_correlation(), async move {
+                    handle_ready_check(&cloned_lcu, &cloned_context, updates, &cfg).await;
+                }),
+            );
+        }
+        GameflowPhase::InProgress => {
+            let history = context.records().history;
+            let cloned_lcu = lcu.clone();
+            context
+                .supervisor()
+                .start(GameflowPhase::InProgress, "lobby_history", async move {
+                    mark_game_started(&cloned_lcu, &history).await
+                });
+
+            let cloned_context = context.clone();
+            context
+                .supervisor()
+                .start(GameflowPhase::InProgress, "in_game_roster", async move {
+                    live_client::reveal_roster(cloned_context.in_game(), &cloned_context).await
+                });
+        }
+        GameflowPhase::EndOfGame => {
+            let history = context.records().history;
+            let cloned_context = context.clone();
+            let cloned_lcu = lcu.clone();
+            context
+                .supervisor()
+                .start(GameflowPhase::EndOfGame, "game_result", async move {
+                    end_of_game::record_game_result(&cloned_lcu, &history, &cloned_context).await;
+                });
         }
         _ => {}
     }
 
-    if let Err(error) = app_handle.emit_all("client_state_update", client_state) {
+    if let Err(error) = context.emit_event("client_state_update", client_state) {
         log_error!("Failed to emit League Client state: {error}");
     }
 }
+
+async fn mark_game_started(lcu: &impl LcuApi, history: &LobbyHistory) {
+    let game_id = match lcu.gameflow_session().await {
+        Ok(gameflow) => gameflow.game_data.game_id,
+        Err(error) => {
+            log_warn!("Failed to read the game in progress: {error}");
+            return;
+        }
+    };
+    if let Err(error) = history.mark_game_started(game_id).await {
+        log_error!("Failed to update lobby history: {error:#}");
+    }
+}
+
+/// Stand-in app for tests.
+#[cfg(test)]
+pub mod fake {
+    use super::*;
+    use crate::emitter::RecordingEmitter;
+    use serde::Serialize;
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    pub struct TestContext(Arc<TestApp>);
+
+    struct TestApp {
+        config: AppConfig,
+        account: ActiveAccount,
+        supervisor: PhaseSupervisor,
+        records: PlayerRecords,
+        in_game: InGame,
+        dodge: Dodge,
+        presets: AppPresets,
+        emitter: RecordingEmitter,
+    }
+
+    impl TestContext {
+        pub fn new(config: Config) -> Self {
+            Self(Arc::new(TestApp {
+                config: AppConfig::new(config),
+                account: ActiveAccount::default(),
+                supervisor: PhaseSupervisor::default(),
+                records: PlayerRecords {
+                    history: LobbyHistory::temporary(),
+                    notes: PlayerNotes::temporary(),
+                },
+                in_game: InGame::default(),
+                dodge: Dodge::default(),
+                presets: AppPresets::new(Vec::new()),
+                emitter: RecordingEmitter::default(),
+            }))
+        }
+
+        /// Everything emitted to the frontend.
+        pub fn emitter(&self) -> &RecordingEmitter {
+            &self.0.emitter
+        }
+    }
+
+    impl Emitter for TestContext {
+        fn emit_event<S: Serialize + Clone>(&self, event: &str, payload: S) -> anyhow::Result<()> {
+            self.0.emitter.emit_event(event, payload)
+        }
+    }
+
+    impl PhaseContext for TestContext {
+        fn supervisor(&self) -> &PhaseSupervisor {
+            &self.0.supervisor
+        }
+
+        fn app_config(&self) -> &AppConfig {
+            &self.0.config
+        }
+
+        fn account(&self) -> &ActiveAccount {
+            &self.0.account
+        }
+
+        fn records(&self) -> PlayerRecords {
+            self.0.records.clone()
+        }
+
+        fn in_game(&self) -> &InGame {
+            &self.0.in_game
+        }
+
+        fn dodge(&self) -> &Dodge {
+            &self.0.dodge
+        }
+
+        fn presets(&self) -> &AppPresets {
+            &self.0.presets
+        }
+    }
+
+    /// Polls `condition` until it holds, failing the test after five seconds.
+    pub async fn eventually(condition: impl Fn() -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !condition() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("condition was not met in time");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        fake::{eventually, TestContext},
+        *,
+    };
+    use crate::{lcu_api::fake::FakeLcuApi, live_client::InGameRoster};
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn reports_a_ready_check_that_ends_while_waiting_to_accept() {
+        let lcu = FakeLcuApi::default();
+        lcu.state().ready_check = Some(
+            serde_json::from_value(json!({ "state": "InProgress", "playerResponse": "None" }))
+                .unwrap(),
+        );
+        let context = TestContext::new(Config {
+            auto_accept: true,
+            accept_delay: 5_000,
+            ..Config::default()
+        });
+        let bus = Arc::new(EventBus::default());
+        register(&bus, &context, &lcu);
+
+        bus.publish_phase(GameflowPhase::ReadyCheck);
+        eventually(|| !context.supervisor().running_tasks().is_empty()).await;
+        bus.publish_phase(GameflowPhase::Matchmaking);
+        eventually(|| !context.emitter().payloads("auto_accept_result").is_empty()).await;
+
+        assert!(lcu.actions().is_empty());
+        assert_eq!(
+            context.emitter().payloads("auto_accept_result"),
+            [json!({ "outcome": "skipped", "reason": "the ready check ended" })]
+        );
+        eventually(|| context.supervisor().running_tasks().is_empty()).await;
+    }
+
+    #[tokio::test]
+    async fn forgets_the_in_game_roster_when_the_game_ends() {
+        let lcu = FakeLcuApi::default();
+        let context = TestContext::new(Config::default());
+        let bus = Arc::new(EventBus::default());
+        register(&bus, &context, &lcu);
+
+        bus.publish_phase(GameflowPhase::InProgress);
+        eventually(|| !context.supervisor().running_tasks().is_empty()).await;
+        *context.in_game().0.lock().await = Some(InGameRoster {
+            allies: Vec::new(),
+            enemies: Vec::new(),
+        });
+        bus.publish_phase(GameflowPhase::EndOfGame);
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while context.in_game().0.lock().await.is_some() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the roster was not cleared");
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// Which of the app's directories a file belongs in.
+#[derive(Clone, Copy, Debug)]
+pub enum Dir {
+    /// Settings, such as the config and presets.
+    Config,
+    /// What Reveal recorded, such as lobby history and player notes.
+    Data,
+}
+
+/// The path of `file_name` in `dir`, creating the directory if needed.
+pub fn path(app_handle: &AppHandle, dir: Dir, file_name: &str) -> Result<PathBuf> {
+    let resolver = app_handle.path_resolver();
+    let dir = match dir {
+        Dir::Config => resolver
+            .app_config_dir()
+            .context("Reveal has no application config directory")?,
+        Dir::Data => resolver
+            .app_data_dir()
+            .context("Reveal has no application data directory")?,
+    };
+    std::fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    Ok(dir.join(file_name))
+}
+
+/// Writes `contents` next to `path` first and then renames it over `path`, so
+/// a crash mid-write leaves the previous file intact.
+pub fn write_sync(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let temp_path = temp_path(path);
+    std::fs::write(&temp_path, contents)
+        .with_context(|| format!("failed to write {}", temp_path.display()))?;
+    std::fs::rename(&temp_path, path)
+        .with_context(|| format!("failed to replace {}", path.display()))
+}
+
+/// The async counterpart of [`write_sync`].
+pub async fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let temp_path = temp_path(path);
+    tokio::fs::write(&temp_path, contents)
+        .await
+        .with_context(|| format!("failed to write {}", temp_path.display()))?;
+    tokio::fs::rename(&temp_path, path)
+        .await
+        .with_context(|| format!("failed to replace {}", path.display()))
+}
+
+/// Moves a file aside so a fresh one can take its place, labelled with why,
+/// e.g. `config.json` to `config.corrupt-20260101-120000.json`.
+pub fn back_up(path: &Path, reason: &str) -> Result<PathBuf> {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let mut file_name = format!("{stem}.{reason}-{}", Utc::now().format("%Y%m%d-%H%M%S"));
+    if let Some(extension) = path.extension() {
+        file_name = format!("{file_name}.{}", extension.to_string_lossy());
+    }
+    let backup_path = path.with_file_name(file_name);
+    std::fs::rename(path, &backup_path)
+        .with_context(|| format!("failed to back up {}", path.display()))?;
+    Ok(backup_path)
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".tmp");
+    path.with_file_name(file_name)
+}
+
+/// A fresh, empty directory in the system temp directory, unique to one test.
+#[cfg(test)]
+pub fn temporary_dir(name: &str) -> PathBuf {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = std::env::temp_dir().join(format!(
+        "reveal-{name}-{}-{}",
+        std::process::id(),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn replaces_files_and_backs_up_unreadable_ones() {
+        let dir = temporary_dir("store");
+        let path = dir.join("notes.json");
+
+        write_sync(&path, "[1]").unwrap();
+        write(&path, "[2]").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[2]");
+        assert!(!temp_path(&path).exists());
+
+        let backup_path = back_up(&path, "corrupt").unwrap();
+        assert!(!path.exists());
+        let backup_name = backup_path.file_name().unwrap().to_string_lossy();
+        assert!(backup_name.starts_with("notes.corrupt-"));
+        assert!(backup_name.ends_with(".json"));
+        assert_eq!(std::fs::read_to_string(backup_path).unwrap(), "[2]");
+    }
+}
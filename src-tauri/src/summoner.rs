@@ -1,19 +1,17 @@
 use serde::{Deserialize, Serialize};
-use shaco::rest::RESTClient;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Summoner {
     pub game_name: String,
     pub tag_line: String,
+    #[serde(default)]
+    pub puuid: String,
 }
 
-pub async fn get_current_summoner(remoting_client: &RESTClient) -> anyhow::Result<Summoner> {
-    let summoner: Summoner = serde_json::from_value(
-        remoting_client
-            .get("/lol-summoner/v1/current-summoner".to_string())
-            .await?,
-    )?;
-
-    Ok(summoner)
+impl Summoner {
+    /// `name#tag`, as shown in the client.
+    pub fn riot_id(&self) -> String {
+        format!("{}#{}", self.game_name, self.tag_line)
+    }
 }
@@ -0,0 +1,216 @@
+use crate::{gameflow::GameflowPhase, logging};
+use serde::Serialize;
+use std::{collections::HashMap, future::Future, sync::Mutex};
+use tokio::{task::JoinHandle, time::Instant};
+
+/// Owns the background work started for gameflow phases.
+///
+/// Each feature runs at most one task per phase: starting it again replaces
+/// the previous task, leaving the phase cancels it unless it was started with
+/// [`start_until_done`](Self::start_until_done), and a disconnect cancels
+/// everything. Tasks are spawned onto the current runtime, so callers must
+/// already be running inside it.
+#[derive(Default)]
+pub struct PhaseSupervisor(Mutex<HashMap<(GameflowPhase, &'static str), SupervisedTask>>);
+
+struct SupervisedTask {
+    started_at: Instant,
+    handle: JoinHandle<()>,
+    cancel_with_phase: bool,
+}
+
+/// A running task, as reported to the debug command.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskInfo {
+    pub phase: GameflowPhase,
+    pub name: &'static str,
+    pub running_for_ms: u64,
+}
+
+impl PhaseSupervisor {
+    /// Runs `task` for `phase`, cancelling the feature's previous task for
+    /// the same phase.
+    pub fn start<F>(&self, phase: GameflowPhase, name: &'static str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn(phase, name, task, true);
+    }
+
+    /// Like [`start`](Self::start), but leaves the task running when `phase`
+    /// ends, for tasks that notice the end themselves and still have
+    /// something to report. A disconnect still cancels it.
+    pub fn start_until_done<F>(&self, phase: GameflowPhase, name: &'static str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn(phase, name, task, false);
+    }
+
+    fn spawn<F>(&self, phase: GameflowPhase, name: &'static str, task: F, cancel_with_phase: bool)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let mut tasks = self.0.lock().unwrap();
+        tasks.retain(|_, task| !task.handle.is_finished());
+
+        let task = SupervisedTask {
+            started_at: Instant::now(),
+            handle: tokio::spawn(logging::inherit(task)),
+            cancel_with_phase,
+        };
+        if let Some(previous) = tasks.insert((phase.clone(), name), task) {
+            log_info!("Replacing the running {name} task for {phase}");
+            previous.handle.abort();
+        }
+    }
+
+    /// Cancels every task started for `phase` with [`start`](Self::start).
+    pub fn cancel_phase(&self, phase: &GameflowPhase) {
+        self.0.lock().unwrap().retain(|(task_phase, name), task| {
+            if task_phase != phase || !task.cancel_with_phase {
+                return true;
+            }
+            if !task.handle.is_finished() {
+                log_info!("Cancelling the {name} task after leaving {phase}");
+                task.handle.abort();
+            }
+            false
+        });
+    }
+
+    /// Cancels every task, e.g. when the client disconnects.
+    pub fn cancel_all(&self) {
+        for ((phase, name), task) in self.0.lock().unwrap().drain() {
+            if !task.handle.is_finished() {
+                log_info!("Cancelling the {name} task for {phase}");
+                task.handle.abort();
+            }
+        }
+    }
+
+    /// Tasks that are still running, oldest first.
+    pub fn running_tasks(&self) -> Vec<TaskInfo> {
+        let mut running: Vec<_> = self
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, task)| !task.handle.is_finished())
+            .map(|((phase, name), task)| (task.started_at, phase.clone(), *name))
+            .collect();
+        running.sort_by_key(|(started_at, _, _)| *started_at);
+
+        running
+            .into_iter()
+            .map(|(started_at, phase, name)| TaskInfo {
+                phase,
+                name,
+                running_for_ms: started_at.elapsed().as_millis() as u64,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    /// Counts how many of the started tasks ran to completion.
+    fn counted_task(finished: &Arc<AtomicUsize>) -> impl Future<Output = ()> + Send + 'static {
+        let finished = finished.clone();
+        async move {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            finished.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn replaces_a_phase_task_when_it_starts_again() {
+        let supervisor = PhaseSupervisor::default();
+        let finished = Arc::new(AtomicUsize::new(0));
+
+        supervisor.start(
+            GameflowPhase::ChampSelect,
+            "tracker",
+            counted_task(&finished),
+        );
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        supervisor.start(
+            GameflowPhase::ChampSelect,
+            "tracker",
+            counted_task(&finished),
+        );
+
+        assert_eq!(
+            supervisor.running_tasks(),
+            [TaskInfo {
+                phase: GameflowPhase::ChampSelect,
+                name: "tracker",
+                running_for_ms: 0,
+            }]
+        );
+        tokio::time::sleep(Duration::from_secs(20)).await;
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+        assert!(supervisor.running_tasks().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancels_only_the_tasks_of_the_phase_that_ended() {
+        let supervisor = PhaseSupervisor::default();
+        let finished = Arc::new(AtomicUsize::new(0));
+        supervisor.start(GameflowPhase::ReadyCheck, "accept", counted_task(&finished));
+        supervisor.start(
+            GameflowPhase::ChampSelect,
+            "tracker",
+            counted_task(&finished),
+        );
+        supervisor.start(GameflowPhase::ChampSelect, "dodge", counted_task(&finished));
+
+        supervisor.cancel_phase(&GameflowPhase::ChampSelect);
+        tokio::time::sleep(Duration::from_secs(20)).await;
+
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lets_tasks_started_until_done_outlive_their_phase() {
+        let supervisor = PhaseSupervisor::default();
+        let finished = Arc::new(AtomicUsize::new(0));
+        supervisor.start_until_done(GameflowPhase::ReadyCheck, "accept", counted_task(&finished));
+
+        supervisor.cancel_phase(&GameflowPhase::ReadyCheck);
+        assert_eq!(supervisor.running_tasks().len(), 1);
+        tokio::time::sleep(Duration::from_secs(20)).await;
+
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancels_everything_on_disconnect() {
+        let supervisor = PhaseSupervisor::default();
+        let finished = Arc::new(AtomicUsize::new(0));
+        supervisor.start(GameflowPhase::ReadyCheck, "accept", counted_task(&finished));
+        supervisor.start(
+            GameflowPhase::ChampSelect,
+            "tracker",
+            counted_task(&finished),
+        );
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(supervisor.running_tasks().len(), 2);
+        supervisor.cancel_all();
+        tokio::time::sleep(Duration::from_secs(20)).await;
+
+        assert_eq!(finished.load(Ordering::SeqCst), 0);
+        assert!(supervisor.running_tasks().is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{
+    emitter::Emitter,
+    lcu_api::LcuApi,
+    match_history::{MatchHistory, RankedStats},
+};
+use anyhow::Result;
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use serde::Serialize;
+use std::cmp::Reverse;
+
+/// Ranked queues in the order their rank is shown.
+const RANKED_QUEUES: [&str; 2] = ["RANKED_SOLO_5x5", "RANKED_FLEX_SR"];
+const TOP_CHAMPIONS: usize = 3;
+
+/// A summary of a player's rank and recent games.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeammateStats {
+    pub puuid: String,
+    pub rank: Option<Rank>,
+    /// Recent games read from match history.
+    pub games: u32,
+    pub wins: u32,
+    /// Rounded percentage, `None` without games.
+    pub win_rate: Option<u32>,
+    /// Most played first.
+    pub top_champions: Vec<ChampionPlays>,
+    /// The most played position, using Champ Select's names.
+    pub main_role: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rank {
+    pub queue: String,
+    pub tier: String,
+    pub division: String,
+    pub league_points: u32,
+    pub wins: u32,
+    pub losses: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChampionPlays {
+    pub champion_id: u32,
+    pub games: u32,
+    pub wins: u32,
+}
+
+pub fn summarize(
+    puuid: &str,
+    history: &MatchHistory,
+    ranked: Option<&RankedStats>,
+) -> TeammateStats {
+    let played: Vec<_> = history
+        .games
+        .games
+        .iter()
+        .filter_map(|game| game.player(puuid))
+        .collect();
+    let games = played.len() as u32;
+    let wins = played.iter().filter(|player| player.stats.win).count() as u32;
+
+    // Counted in match history order so ties go to the more recent pick.
+    let mut champions: Vec<ChampionPlays> = Vec::new();
+    let mut roles: Vec<(&str, u32)> = Vec::new();
+    for player in &played {
+        match champions
+            .iter_mut()
+            .find(|plays| plays.champion_id == player.champion_id)
+        {
+            Some(plays) => {
+                plays.games += 1;
+                plays.wins += player.stats.win as u32;
+            }
+            None => champions.push(ChampionPlays {
+                champion_id: player.champion_id,
+                games: 1,
+                wins: player.stats.win as u32,
+            }),
+        }
+
+        if let Some(position) = player.position() {
+            match roles.iter_mut().find(|(role, _)| *role == position) {
+                Some((_, count)) => *count += 1,
+                None => roles.push((position, 1)),
+            }
+        }
+    }
+    champions.sort_by_key(|plays| Reverse(plays.games));
+    champions.truncate(TOP_CHAMPIONS);
+    roles.sort_by_key(|(_, count)| Reverse(*count));
+
+    TeammateStats {
+        puuid: puuid.to_string(),
+        rank: ranked.and_then(rank),
+        games,
+        wins,
+        win_rate: (games > 0).then(|| (wins as f64 * 100.0 / games as f64).round() as u32),
+        top_champions: champions,
+        main_role: roles.first().map(|(role, _)| role.to_string()),
+    }
+}
+
+fn rank(ranked: &RankedStats) -> Option<Rank> {
+    RANKED_QUEUES.iter().find_map(|queue| {
+        let stats = ranked.queue_map.get(*queue)?;
+        if stats.tier.is_empty() || stats.tier == "NONE" || stats.tier == "UNRANKED" {
+            return None;
+        }
+
+        Some(Rank {
+            queue: queue.to_string(),
+            tier: stats.tier.clone(),
+            division: stats.division.clone(),
+            league_points: stats.league_points,
+            wins: stats.wins,
+            losses: stats.losses,
+        })
+    })
+}
+
+async fn fetch(lcu: &impl LcuApi, puuid: &str) -> Result<TeammateStats> {
+    let (history, ranked) = tokio::join!(lcu.match_history(puuid), lcu.ranked_stats(puuid));
+    let history = history?;
+    let ranked = ranked
+        .map_err(|error| log_warn!("Ranked stats are not available: {error}"))
+        .ok();
+
+    Ok(summarize(puuid, &history, ranked.as_ref()))
+}
+
+/// Looks up every player at once and emits `teammate_stats` for each one as
+/// soon as their lookup finishes. Players whose history cannot be read are
+/// skipped.
+pub async fn emit_teammate_stats(lcu: &impl LcuApi, emitter: &impl Emitter, puuids: &[String]) {
+    let mut unique: Vec<&str> = Vec::new();
+    for puuid in puuids.iter().filter(|puuid| !puuid.is_empty()) {
+        if !unique.contains(&puuid.as_str()) {
+            unique.push(puuid);
+        }
+    }
+    log_info!("Looking up stats for {} players", unique.len());
+
+    let mut lookups: FuturesUnordered<_> = unique
+        .into_iter()
+        .map(|puuid| async move { (puuid, fetch(lcu, puuid).await) })
+        .collect();
+    while let Some((puuid, stats)) = lookups.next().await {
+        match stats {
+            Ok(stats) => {
+                if let Err(error) = emitter.emit_event("teammate_stats", stats) {
+                    log_error!("Failed to emit teammate stats: {error}");
+                }
+            }
+            Err(error) => log_warn!("Failed to look up stats for {puuid}: {error:#}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{emitter::RecordingEmitter, lcu_api::fake::FakeLcuApi};
+
+    fn history() -> MatchHistory {
+        serde_json::from_str(include_str!("../fixtures/match-history/jungle_main.json")).unwrap()
+    }
+
+    fn ranked() -> RankedStats {
+        serde_json::from_str(include_str!("../fixtures/match-history/ranked_stats.json")).unwrap()
+    }
+
+    #[test]
+    fn summarizes_rank_win_rate_champions_and_role() {
+        let stats = summarize("jungle-puuid", &history(), Some(&ranked()));
+
+        assert_eq!(
+            stats,
+            TeammateStats {
+                puuid: "jungle-puuid".to_string(),
+                rank: Some(Rank {
+                    queue: "RANKED_SOLO_5x5".to_string(),
+                    tier: "GOLD".to_string(),
+                    division: "II".to_string(),
+                    league_points: 45,
+                    wins: 34,
+                    losses: 30,
+                }),
+                games: 8,
+                wins: 5,
+                win_rate: Some(63),
+                top_champions: vec![
+                    ChampionPlays {
+                        champion_id: 64,
+                        games: 4,
+                        wins: 3,
+                    },
+                    ChampionPlays {
+                        champion_id: 121,
+                        games: 2,
+                        wins: 1,
+                    },
+                    ChampionPlays {
+                        champion_id: 157,
+                        games: 1,
+                        wins: 1,
+                    },
+                ],
+                main_role: Some("jungle".to_string()),
+            }
+        );
+
+        let unranked = summarize("new-puuid", &MatchHistory::default(), None);
+        assert_eq!(unranked.win_rate, None);
+        assert_eq!(unranked.main_role, None);
+    }
+
+    #[tokio::test]
+    async fn emits_stats_for_each_player_that_could_be_looked_up() {
+        let lcu = FakeLcuApi::default();
+        lcu.state()
+            .match_histories
+            .insert("jungle-puuid".to_string(), history());
+        let emitter = RecordingEmitter::default();
+        let puuids = ["jungle-puuid", "jungle-puuid", "missing-puuid", ""].map(String::from);
+
+        emit_teammate_stats(&lcu, &emitter, &puuids).await;
+
+        let payloads = emitter.payloads("teammate_stats");
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0]["puuid"], "jungle-puuid");
+        assert_eq!(payloads[0]["rank"], serde_json::Value::Null);
+        assert_eq!(payloads[0]["mainRole"], "jungle");
+    }
+}
@@ -1,84 +1,42 @@
-use crate::lobby::{Lobby, Participant};
-use urlencoding::encode;
-
-pub fn create_opgg_link(summoners: &[Participant], region: &str) -> String {
-    let base_url = format!("https://www.op.gg/multisearch/{}?summoners=", region);
-    let participants = join_participants(summoners, '#');
-    let encoded_path = encode(&participants);
-    format!("{}{}", base_url, encoded_path)
-}
-
-pub fn create_deeplol_link(summoners: &[Participant], region: &str) -> String {
-    let base_url = format!("https://deeplol.gg/multi/{}/", region);
-    let participants = join_participants(summoners, '#');
-    let encoded_path = encode(&participants);
-    format!("{}{}", base_url, encoded_path)
-}
-
-pub fn create_ugg_link(summoners: &[Participant], region: &str) -> String {
-    let base_url = format!("https://u.gg/multisearch?region={}", region.to_lowercase());
-    let participants = join_participants(summoners, '-');
-    let encoded_path = encode(&participants);
-    format!("{}&summoners={}", base_url, encoded_path)
-}
-
-pub fn create_tracker_link(summoners: &[Participant], region: &str) -> String {
-    let base_url = format!("https://tracker.gg/lol/multisearch/{}/", region);
-    let participants = join_participants(summoners, '#');
-    let encoded_path = encode(&participants);
-    format!("{}{}", base_url, encoded_path)
-}
-
-pub fn create_fow_link(summoners: &[Participant], region: &str) -> String {
-    let base_url = format!("https://www.fow.lol/multi/{}#", region.to_lowercase());
-    let participants = join_participants(summoners, '#');
-    let encoded_path = encode(&participants);
-    format!("{}{}", base_url, encoded_path)
-}
-
-pub fn create_multi_link(lobby: &Lobby, region: &str, site: &str) -> anyhow::Result<String> {
+use crate::{
+    config::Config,
+    lobby::Lobby,
+    multi_search::{self, MultiSearchProvider},
+    region::Region,
+};
+
+pub fn create_multi_link(
+    lobby: &Lobby,
+    region: &Region,
+    provider: &dyn MultiSearchProvider,
+) -> anyhow::Result<String> {
     if lobby.participants.is_empty() {
         anyhow::bail!("cannot create a multi link without participants");
     }
 
-    let link = match site {
-        "opgg" => create_opgg_link(&lobby.participants, region),
-        "deeplol" => create_deeplol_link(&lobby.participants, region),
-        "ugg" => create_ugg_link(&lobby.participants, &format!("{}1", region)),
-        "tracker" => create_tracker_link(&lobby.participants, region),
-        "fow" => create_fow_link(&lobby.participants, region),
-        _ => anyhow::bail!("unsupported multi provider: {site}"),
-    };
-
-    Ok(link)
+    provider.link(&lobby.participants, region)
 }
 
-pub fn display_champ_select(lobby: &Lobby, region: &str, site: &str) -> anyhow::Result<()> {
+pub fn display_champ_select(lobby: &Lobby, region: &Region, config: &Config) -> anyhow::Result<()> {
     log_info!(
-        "Opening {site} multi-search for {} participants in region {region}",
+        "Opening {} multi-search for {} participants in region {region}",
+        config.multi_provider,
         lobby.participants.len()
     );
-    let link = create_multi_link(lobby, region, site)?;
+    let provider = multi_search::selected(config)?;
+    let link = create_multi_link(lobby, region, provider.as_ref())?;
     open::that(link)?;
     Ok(())
 }
 
-fn join_participants(participants: &[Participant], tag_separator: char) -> String {
-    participants
-        .iter()
-        .map(|participant| {
-            format!(
-                "{}{}{}",
-                participant.game_name, tag_separator, participant.game_tag
-            )
-        })
-        .collect::<Vec<_>>()
-        .join(",")
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{lobby::Participant, multi_search::Providers};
+
+    fn link(lobby: &Lobby, region: &Region, provider: &str) -> anyhow::Result<String> {
+        create_multi_link(lobby, region, Providers::builtin().get(provider).unwrap())
+    }
 
     fn lobby() -> Lobby {
         Lobby {
@@ -94,6 +52,8 @@ mod tests {
                 assigned_position: None,
                 cell_id: None,
                 pick_turn: None,
+                played_with: None,
+                note: None,
             }],
         }
     }
@@ -103,17 +63,21 @@ mod tests {
         let lobby = lobby();
 
         assert_eq!(
-            create_multi_link(&lobby, "NA", "opgg").unwrap(),
+            link(&lobby, &Region::Na, "opgg").unwrap(),
             "https://www.op.gg/multisearch/NA?summoners=Player%20One%23NA1"
         );
         assert_eq!(
-            create_multi_link(&lobby, "NA", "ugg").unwrap(),
+            link(&lobby, &Region::Na, "ugg").unwrap(),
             "https://u.gg/multisearch?region=na1&summoners=Player%20One-NA1"
         );
         assert_eq!(
-            create_multi_link(&lobby, "SG", "fow").unwrap(),
+            link(&lobby, &Region::Sg, "fow").unwrap(),
             "https://www.fow.lol/multi/sg#Player%20One%23NA1"
         );
+        assert_eq!(
+            link(&lobby, &Region::Other("NEW1".to_string()), "ugg").unwrap(),
+            "https://u.gg/multisearch?region=NEW1&summoners=Player%20One-NA1"
+        );
     }
 
     #[test]
@@ -131,24 +95,27 @@ mod tests {
             assigned_position: None,
             cell_id: None,
             pick_turn: None,
+            played_with: None,
+            note: None,
         });
 
         assert_eq!(
-            create_multi_link(&lobby, "SG", "fow").unwrap(),
+            link(&lobby, &Region::Sg, "fow").unwrap(),
             "https://www.fow.lol/multi/sg#Player%20One%23NA1%2CSecond%20Player%230911"
         );
     }
 
     #[test]
     fn rejects_invalid_link_requests() {
-        assert!(create_multi_link(
+        assert!(link(
             &Lobby {
                 participants: vec![]
             },
-            "NA",
+            &Region::Na,
             "opgg"
         )
         .is_err());
-        assert!(create_multi_link(&lobby(), "NA", "unknown").is_err());
+        assert!(link(&lobby(), &Region::Pbe, "opgg").is_err());
+        assert!(Providers::builtin().get("unknown").is_none());
     }
 }
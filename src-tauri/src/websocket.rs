@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    connect_async_tls_with_config,
+    tungstenite::{client::IntoClientRequest, http::HeaderValue, Message},
+    Connector, MaybeTlsStream, WebSocketStream,
+};
+
+const WAMP_SUBSCRIBE: u8 = 5;
+const WAMP_EVENT: u8 = 8;
+const JSON_API_EVENT: &str = "OnJsonApiEvent";
+
+/// A single `OnJsonApiEvent` pushed by the League Client.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LcuEvent {
+    pub uri: String,
+    pub event_type: String,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+/// WAMP connection to the League Client's event socket.
+///
+/// Connects with explicit credentials so the same client works against the
+/// real League Client and the mock LCU used in tests.
+pub struct LcuWebsocket {
+    stream: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl LcuWebsocket {
+    pub async fn connect(port: u16, auth_token: &str) -> Result<Self> {
+        let mut request = format!("wss://127.0.0.1:{port}")
+            .into_client_request()
+            .context("failed to build websocket request")?;
+        let credentials = STANDARD.encode(format!("riot:{auth_token}"));
+        request.headers_mut().insert(
+            "Authorization",
+            HeaderValue::from_str(&format!("Basic {credentials}"))
+                .context("invalid websocket credentials")?,
+        );
+
+        // The League Client serves a certificate signed by Riot's own root.
+        let connector = native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .build()
+            .context("failed to build TLS connector")?;
+        let (stream, _) =
+            connect_async_tls_with_config(request, None, Some(Connector::NativeTls(connector)))
+                .await
+                .context("failed to connect to League Client websocket")?;
+
+        Ok(Self { stream })
+    }
+
+    pub async fn subscribe(&mut self, uri: &str) -> Result<()> {
+        let message = serde_json::json!([WAMP_SUBSCRIBE, event_name(uri)]).to_string();
+        self.stream
+            .send(Message::Text(message))
+            .await
+            .with_context(|| format!("failed to subscribe to {uri}"))
+    }
+
+    /// Waits for the next JSON API event, or `None` once the socket closes.
+    pub async fn next_event(&mut self) -> Option<LcuEvent> {
+        while let Some(message) = self.stream.next().await {
+            let text = match message {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => return None,
+                Ok(_) => continue,
+                Err(error) => {
+                    log_warn!("League websocket read failed: {error}");
+                    return None;
+                }
+            };
+
+            if let Some(event) = parse_event(&text) {
+                return Some(event);
+            }
+        }
+
+        None
+    }
+}
+
+/// Converts an LCU resource path into its WAMP topic name.
+pub fn event_name(uri: &str) -> String {
+    format!("{JSON_API_EVENT}{}", uri.replace('/', "_"))
+}
+
+fn parse_event(text: &str) -> Option<LcuEvent> {
+    let (opcode, _topic, event): (u8, String, LcuEvent) = serde_json::from_str(text).ok()?;
+    (opcode == WAMP_EVENT).then_some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_wamp_topics_from_resource_paths() {
+        assert_eq!(
+            event_name("/lol-gameflow/v1/gameflow-phase"),
+            "OnJsonApiEvent_lol-gameflow_v1_gameflow-phase"
+        );
+    }
+
+    #[test]
+    fn parses_json_api_events() {
+        let event = parse_event(
+            r#"[8,"OnJsonApiEvent_lol-gameflow_v1_gameflow-phase",{"data":"ChampSelect","eventType":"Update","uri":"/lol-gameflow/v1/gameflow-phase"}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(event.uri, "/lol-gameflow/v1/gameflow-phase");
+        assert_eq!(event.event_type, "Update");
+        assert_eq!(event.data, "ChampSelect");
+        assert!(parse_event(r#"[0,"session-id",1,"server"]"#).is_none());
+    }
+}
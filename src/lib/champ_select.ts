@@ -1,3 +1,5 @@
+import type { PlayerNote } from "$lib/player_notes";
+
 export interface ChampSelect {
   participants: Participant[];
 }
@@ -14,6 +16,101 @@ export interface Participant {
   assigned_position?: string;
   cell_id?: number;
   pick_turn?: number;
+  played_with?: PlayedWith;
+  note?: PlayerNote;
+}
+
+export type RoleFlag =
+  | {
+      kind: "off_role";
+      position: string;
+      position_games: number;
+      games: number;
+    }
+  | { kind: "first_time_champion"; champion_id: number; games: number };
+
+export function formatRoleFlag(flag: RoleFlag): string {
+  if (flag.kind === "off_role") {
+    const position = formatAssignedPosition(flag.position);
+    return `Assigned ${position}, ${flag.position_games} of last ${flag.games} games as ${position}`;
+  }
+  return `No games on this champion in the last ${flag.games}`;
+}
+
+export interface PlayedWith {
+  games: number;
+  wins: number;
+  losses: number;
+  last_played_at: string;
+  /** null when the result of that game is unknown. */
+  last_won: boolean | null;
+}
+
+export function formatPlayedWith(playedWith: PlayedWith): string {
+  const games = playedWith.games === 1 ? "1 game" : `${playedWith.games} games`;
+  if (playedWith.wins + playedWith.losses === 0) return `Met in ${games}`;
+  return `Met in ${games} (${playedWith.wins}W ${playedWith.losses}L)`;
+}
+
+export interface ChampSelectTeamMember {
+  assignedPosition: string;
+  cellId: number;
+  pickTurn: number;
+  puuid: string;
+  championId: number;
+  championPickIntent: number;
+  spell1Id: number;
+  spell2Id: number;
+}
+
+export interface ChampSelectAction {
+  id: number;
+  actorCellId: number;
+  championId: number;
+  completed: boolean;
+  isInProgress: boolean;
+  isAllyAction: boolean;
+  type: "pick" | "ban" | "ten_bans_reveal" | "other";
+}
+
+export interface ChampSelectSession {
+  gameId: number;
+  timer: { adjustedTimeLeftInPhase: number; phase: string };
+  localPlayerCellId: number;
+  isSpectating: boolean;
+  myTeam: ChampSelectTeamMember[];
+  theirTeam: ChampSelectTeamMember[];
+  actions: ChampSelectAction[][];
+  bans: { myTeamBans: number[]; theirTeamBans: number[]; numBans: number };
+  benchEnabled: boolean;
+  benchChampions: { championId: number; isPriority: boolean }[];
+}
+
+export type DraftStatus = "Banning" | "Picking" | "Hovering" | "Locked";
+
+export function draftStatus(
+  session: ChampSelectSession | null,
+  cellId: number | undefined,
+): DraftStatus | null {
+  if (!session || cellId === undefined) return null;
+
+  const actions = session.actions
+    .flat()
+    .filter((action) => action.actorCellId === cellId);
+  const current = actions.find((action) => action.isInProgress);
+  if (current?.type === "ban") return "Banning";
+  if (current?.type === "pick") return "Picking";
+
+  if (actions.some((action) => action.type === "pick" && action.completed)) {
+    return "Locked";
+  }
+
+  const member = session.myTeam.find((member) => member.cellId === cellId);
+  if (member && (member.championId || member.championPickIntent)) {
+    return "Hovering";
+  }
+
+  return null;
 }
 
 const POSITION_LABELS: Record<string, string> = {
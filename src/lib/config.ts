@@ -1,7 +1,9 @@
 import { invoke } from "@tauri-apps/api/tauri";
 
-export type MultiProvider = "opgg" | "deeplol" | "ugg" | "tracker" | "fow";
+/** A built-in provider ID or the ID of a custom provider. */
+export type MultiProvider = string;
 
+/** The built-in providers, used when the backend is not available. */
 export const MULTI_PROVIDERS: ReadonlyArray<{
     label: string;
     value: MultiProvider;
@@ -13,24 +15,148 @@ export const MULTI_PROVIDERS: ReadonlyArray<{
     { label: "FOW.LOL", value: "fow" },
 ];
 
+export interface ProviderInfo {
+    id: string;
+    name: string;
+    custom: boolean;
+}
+
+/**
+ * A multi-search site defined by a URL template. `url` uses `{region}`,
+ * `{platform}` and `{riotIds}`, optionally with `:lower` or `:upper`, e.g.
+ * `{region:lower}`.
+ */
+export interface CustomProvider {
+    id: string;
+    name: string;
+    url: string;
+    tagSeparator: string;
+    listSeparator: string;
+}
+
+export interface HourRange {
+    start: number;
+    end: number;
+}
+
+export interface ReadyCheckRules {
+    acceptQueueIds: number[];
+    blacklistedQueueIds: number[];
+    doNotDisturb: boolean;
+    declineHours: HourRange | null;
+}
+
+export interface AutoDraft {
+    autoBan: boolean;
+    autoPick: boolean;
+    lockAtSecondsLeft: number;
+    /** Champion IDs by assigned position, or "any". */
+    bans: Record<string, number[]>;
+    picks: Record<string, number[]>;
+}
+
+/** Settings for some accounts; unset fields keep the top-level value. */
+export interface Profile {
+    name: string;
+    /** Riot IDs (`name#tag`) or puuids. */
+    accounts: string[];
+    autoOpen?: boolean;
+    autoOpenDelaySeconds?: number;
+    autoAccept?: boolean;
+    acceptDelay?: number;
+    multiProvider?: MultiProvider;
+}
+
+/** The logged-in account and the profile applied to it, if any. */
+export interface ActiveProfile {
+    riotId: string;
+    profile: string | null;
+}
+
 export interface Config {
+    /** Config layout version, managed by the backend. */
+    version: number;
     autoOpen: boolean;
     autoOpenDelaySeconds: number;
     autoAccept: boolean;
     acceptDelay: number;
     multiProvider: MultiProvider;
+    customProviders: CustomProvider[];
+    readyCheckRules: ReadyCheckRules;
+    autoDraft: AutoDraft;
+    profiles: Profile[];
+}
+
+/** The saved settings, which the settings page edits, and what applies with
+ * the active profile. Sent by `get_config` and `config_updated`. */
+export interface ConfigView {
+    config: Config;
+    effective: Config;
 }
 
 export const DEFAULT_CONFIG: Config = {
+    version: 1,
     autoOpen: true,
     autoOpenDelaySeconds: 6,
     autoAccept: false,
     acceptDelay: 2_000,
     multiProvider: "opgg",
+    customProviders: [],
+    readyCheckRules: {
+        acceptQueueIds: [],
+        blacklistedQueueIds: [],
+        doNotDisturb: false,
+        declineHours: null,
+    },
+    autoDraft: {
+        autoBan: false,
+        autoPick: false,
+        lockAtSecondsLeft: 5,
+        bans: {},
+        picks: {},
+    },
+    profiles: [],
 };
 
-export async function updateConfig(config: Config): Promise<void> {
-    await invoke<void>("set_config", {
+/** Reads a comma- or space-separated list of IDs, ignoring anything else. */
+export function parseIds(text: string): number[] {
+    return text
+        .split(/[\s,]+/)
+        .map(Number)
+        .filter((id) => Number.isInteger(id) && id > 0);
+}
+
+export function formatIds(ids: number[]): string {
+    return ids.join(", ");
+}
+
+/** A config value the backend rejected, e.g. `readyCheckRules.declineHours`. */
+export interface FieldError {
+    field: string;
+    message: string;
+}
+
+/** What `set_config` rejects with; `fields` is empty for I/O errors. */
+export interface ConfigError {
+    message: string;
+    fields: FieldError[];
+}
+
+/** Saves the config, resolving to what was stored after clamping. */
+export async function updateConfig(config: Config): Promise<Config> {
+    return invoke<Config>("set_config", {
         newCfg: config,
     });
 }
+
+export async function loadConfig(): Promise<ConfigView> {
+    return invoke<ConfigView>("get_config");
+}
+
+export async function loadActiveProfile(): Promise<ActiveProfile | null> {
+    return invoke<ActiveProfile | null>("get_active_profile");
+}
+
+export async function loadMultiSearchProviders(): Promise<ProviderInfo[]> {
+    return invoke<ProviderInfo[]>("get_multi_search_providers");
+}
@@ -1,5 +1,6 @@
 import { writeText } from "@tauri-apps/api/clipboard";
 import { open } from "@tauri-apps/api/shell";
+import { invoke } from "@tauri-apps/api/tauri";
 import type { Participant } from "$lib/champ_select";
 import type { MultiProvider } from "$lib/config";
 import { isTauriRuntime } from "$lib/runtime";
@@ -46,6 +47,22 @@ export function createMultiSearchLink(
   return `https://www.op.gg/multisearch/${region}?summoners=${encodeURIComponent(joinedParticipants(participants, "#"))}`;
 }
 
+/**
+ * Builds the link with the backend, which also knows the user's custom
+ * providers, falling back to the built-in providers outside the app.
+ */
+export async function multiSearchLink(
+  participants: Participant[],
+  provider: MultiProvider,
+): Promise<string> {
+  if (!isTauriRuntime()) return createMultiSearchLink(participants, provider);
+
+  return invoke<string>("create_multi_search_link", {
+    participants,
+    region: lobbyRegion(participants),
+  });
+}
+
 export function createOpggProfileLink(participant: Participant): string {
   const region = participant.region === "SG2" ? "SG" : participant.region;
   const profile = `${participant.game_name}-${participant.game_tag}`;
@@ -0,0 +1,22 @@
+import { invoke } from "@tauri-apps/api/tauri";
+
+/** Both teams of the game in progress, sent as `in_game_roster`. */
+export interface InGameRoster {
+  allies: RosterPlayer[];
+  enemies: RosterPlayer[];
+}
+
+export interface RosterPlayer {
+  gameName: string;
+  gameTag: string;
+  championName: string;
+  /** e.g. `JUNGLE`; empty in modes without positions. */
+  position: string;
+  summonerSpells: [string, string];
+  isBot: boolean;
+}
+
+/** Opens the multi-search for the enemy team once the game has loaded. */
+export async function openEnemyTeamLink(): Promise<void> {
+  await invoke<void>("open_enemy_team_link");
+}
@@ -1,40 +1,74 @@
-import type { ChampSelect, Participant } from "$lib/champ_select";
-import { logFrontendError } from "$lib/logging";
+import { invoke } from "@tauri-apps/api/tauri";
+import type { Participant } from "$lib/champ_select";
+import { logFrontendError, logFrontendInfo } from "$lib/logging";
 
+/** Where the frontend kept the history before the backend owned it. */
 const STORAGE_KEY = "reveal:lobby-history";
-export const MAX_HISTORY_ITEMS = 50;
 
 export interface LobbyHistoryEntry {
-  id: string;
+  gameId: number;
+  /** -1 when the queue could not be read. */
+  queueId: number;
+  region: string;
   revealedAt: string;
+  updatedAt: string;
   gameStartedAt?: string;
+  result?: GameResult;
   participants: Participant[];
 }
 
-function participantKey(participant: Participant): string {
-  return (
-    participant.puuid ||
-    `${participant.game_name}#${participant.game_tag}:${participant.region}`
-  ).toLowerCase();
+export interface GameResult {
+  won: boolean;
+  championId: number;
+  championName: string;
+  kills: number;
+  deaths: number;
+  assists: number;
+  gameLengthSeconds: number;
 }
 
-function mergeParticipants(
-  current: Participant[],
-  incoming: Participant[],
-): Participant[] {
-  const participants = new Map<string, Participant>();
+export function formatGameResult(result: GameResult): string {
+  const minutes = Math.floor(result.gameLengthSeconds / 60);
+  const seconds = String(result.gameLengthSeconds % 60).padStart(2, "0");
+  const kda = `${result.kills}/${result.deaths}/${result.assists}`;
+  return `${result.championName} ${kda} · ${minutes}:${seconds}`;
+}
 
-  for (const participant of [...current, ...incoming]) {
-    participants.set(participantKey(participant), participant);
-  }
+export async function loadLobbyHistory(): Promise<LobbyHistoryEntry[]> {
+  return invoke<LobbyHistoryEntry[]>("get_lobby_history");
+}
+
+export async function searchLobbyHistory(
+  riotId: string,
+): Promise<LobbyHistoryEntry[]> {
+  return invoke<LobbyHistoryEntry[]>("search_lobby_history", { riotId });
+}
+
+export async function deleteLobbyHistoryEntry(gameId: number): Promise<boolean> {
+  return invoke<boolean>("delete_lobby_history_entry", { gameId });
+}
+
+export async function clearLobbyHistory(): Promise<void> {
+  await invoke<void>("clear_lobby_history");
+}
+
+/** The whole history as a JSON array, most recent first. */
+export async function exportLobbyHistory(): Promise<string> {
+  return invoke<string>("export_lobby_history");
+}
 
-  return Array.from(participants.values()).slice(0, 5);
+/** An entry of the localStorage history, which had no game IDs. */
+interface LegacyLobbyHistoryEntry {
+  id: string;
+  revealedAt: string;
+  gameStartedAt?: string;
+  participants: Participant[];
 }
 
-function isHistoryEntry(value: unknown): value is LobbyHistoryEntry {
+function isLegacyEntry(value: unknown): value is LegacyLobbyHistoryEntry {
   if (!value || typeof value !== "object") return false;
 
-  const entry = value as Partial<LobbyHistoryEntry>;
+  const entry = value as Partial<LegacyLobbyHistoryEntry>;
   return (
     typeof entry.id === "string" &&
     typeof entry.revealedAt === "string" &&
@@ -42,78 +76,34 @@ function isHistoryEntry(value: unknown): value is LobbyHistoryEntry {
   );
 }
 
-function persist(entries: LobbyHistoryEntry[]): LobbyHistoryEntry[] {
-  const nextEntries = entries.slice(0, MAX_HISTORY_ITEMS);
+/**
+ * Moves a history left in localStorage by older versions into the backend,
+ * then removes it. Does nothing once the key is gone.
+ */
+export async function importLegacyLobbyHistory(): Promise<void> {
+  const stored = localStorage.getItem(STORAGE_KEY);
+  if (stored === null) return;
 
+  let entries: LegacyLobbyHistoryEntry[] = [];
   try {
-    localStorage.setItem(STORAGE_KEY, JSON.stringify(nextEntries));
-  } catch (error) {
-    logFrontendError("Failed to save lobby history", error);
-  }
-
-  return nextEntries;
-}
-
-export function createLobbyHistoryId(): string {
-  if (typeof crypto !== "undefined" && "randomUUID" in crypto) {
-    return crypto.randomUUID();
-  }
-
-  return `${Date.now()}-${Math.random().toString(16).slice(2)}`;
-}
-
-export function loadLobbyHistory(): LobbyHistoryEntry[] {
-  try {
-    const stored = localStorage.getItem(STORAGE_KEY);
-    if (!stored) return [];
-
     const parsed: unknown = JSON.parse(stored);
-    if (!Array.isArray(parsed)) return [];
-
-    return parsed.filter(isHistoryEntry).slice(0, MAX_HISTORY_ITEMS);
+    if (Array.isArray(parsed)) entries = parsed.filter(isLegacyEntry);
   } catch (error) {
-    logFrontendError("Failed to load lobby history", error);
-    return [];
+    logFrontendError("Discarding unreadable localStorage lobby history", error);
   }
-}
 
-export function recordLobbyReveal(
-  lobbyId: string,
-  champSelect: ChampSelect,
-): LobbyHistoryEntry[] {
-  const entries = loadLobbyHistory();
-  const existing = entries.find((entry) => entry.id === lobbyId);
-  const entry: LobbyHistoryEntry = {
-    id: lobbyId,
-    revealedAt: existing?.revealedAt ?? new Date().toISOString(),
-    gameStartedAt: existing?.gameStartedAt,
-    participants: mergeParticipants(
-      existing?.participants ?? [],
-      champSelect.participants,
-    ),
-  };
-
-  return persist([entry, ...entries.filter((item) => item.id !== lobbyId)]);
-}
-
-export function markLobbyGameStarted(lobbyId: string): LobbyHistoryEntry[] {
-  const entries = loadLobbyHistory();
-  const startedAt = new Date().toISOString();
-  let changed = false;
-
-  const nextEntries = entries.map((entry) => {
-    if (entry.id !== lobbyId || entry.gameStartedAt) return entry;
-    changed = true;
-    return { ...entry, gameStartedAt: startedAt };
-  });
-
-  return changed ? persist(nextEntries) : entries;
-}
-
-export function clearLobbyHistory(): void {
-  try {
-    localStorage.removeItem(STORAGE_KEY);
-  } catch (error) {
-    logFrontendError("Failed to clear lobby history", error);
+  if (entries.length > 0) {
+    const imported = await invoke<number>("import_lobby_history", {
+      // Older versions did not always store a display name
+      entries: entries.map((entry) => ({
+        ...entry,
+        participants: entry.participants.map((participant) => ({
+          ...participant,
+          name: participant.name ?? "",
+        })),
+      })),
+    });
+    logFrontendInfo(`Imported ${imported} lobby history entries from localStorage`);
   }
+  localStorage.removeItem(STORAGE_KEY);
 }
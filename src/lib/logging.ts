@@ -1,7 +1,10 @@
 import { invoke } from "@tauri-apps/api/tauri";
 import { isTauriRuntime } from "$lib/runtime";
 
-type LogLevel = "info" | "warn" | "error";
+type LogLevel = "info" | "warn" | "error" | "debug";
+
+/** How verbose the backend log file is, most severe first. */
+export type BackendLogLevel = "error" | "warn" | "info" | "debug" | "trace";
 
 function describeError(error: unknown): string {
   if (error instanceof Error) return error.stack || error.message;
@@ -19,6 +22,15 @@ function writeLog(level: LogLevel, message: string) {
   void invoke<void>("write_frontend_log", { level, message }).catch(() => {});
 }
 
+export async function setBackendLogLevel(level: BackendLogLevel): Promise<void> {
+  await invoke<void>("set_log_level", { level });
+}
+
+export function logFrontendDebug(message: string) {
+  console.debug(message);
+  writeLog("debug", message);
+}
+
 export function logFrontendInfo(message: string) {
   console.info(message);
   writeLog("info", message);
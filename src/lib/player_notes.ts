@@ -0,0 +1,39 @@
+import { invoke } from "@tauri-apps/api/tauri";
+
+export interface PlayerNote {
+  puuid: string;
+  riot_id: string;
+  note: string;
+  tags: string[];
+  updated_at: string;
+}
+
+export async function getPlayerNotes(): Promise<PlayerNote[]> {
+  return invoke<PlayerNote[]>("get_player_notes");
+}
+
+export async function savePlayerNote(
+  puuid: string,
+  riotId: string,
+  note: string,
+  tags: string[],
+): Promise<PlayerNote> {
+  return invoke<PlayerNote>("save_player_note", { puuid, riotId, note, tags });
+}
+
+export async function deletePlayerNote(puuid: string): Promise<boolean> {
+  return invoke<boolean>("delete_player_note", { puuid });
+}
+
+/** Notes keyed by puuid, for looking them up while rendering players. */
+export function notesByPuuid(notes: PlayerNote[]): Record<string, PlayerNote> {
+  return Object.fromEntries(notes.map((note) => [note.puuid, note]));
+}
+
+/** Splits comma-separated tags; the backend trims and deduplicates them. */
+export function parseTags(text: string): string[] {
+  return text
+    .split(",")
+    .map((tag) => tag.trim())
+    .filter(Boolean);
+}
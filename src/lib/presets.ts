@@ -0,0 +1,29 @@
+import { invoke } from "@tauri-apps/api/tauri";
+
+export interface RunePage {
+    name: string;
+    primaryStyleId: number;
+    subStyleId: number;
+    selectedPerkIds: number[];
+}
+
+export interface Preset {
+    championId: number;
+    /** Assigned position, or null for every position. */
+    position: string | null;
+    spells: [number, number] | null;
+    runePage: RunePage | null;
+}
+
+export async function getPresets(): Promise<Preset[]> {
+    return invoke<Preset[]>("get_presets");
+}
+
+/** Returns how many presets were imported. */
+export async function importPresets(json: string): Promise<number> {
+    return invoke<number>("import_presets", { json });
+}
+
+export async function exportPresets(): Promise<string> {
+    return invoke<string>("export_presets");
+}
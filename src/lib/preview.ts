@@ -40,14 +40,20 @@ export function getPreviewState(): PreviewState {
       activePage: "history",
       history: [
         {
-          id: "preview-history-current",
+          gameId: 2,
+          queueId: 420,
+          region: "NA",
           revealedAt: new Date().toISOString(),
+          updatedAt: new Date().toISOString(),
           gameStartedAt: new Date().toISOString(),
           participants: PREVIEW_PARTICIPANTS,
         },
         {
-          id: "preview-history-earlier",
+          gameId: 1,
+          queueId: 420,
+          region: "NA",
           revealedAt: new Date(Date.now() - 3_600_000).toISOString(),
+          updatedAt: new Date(Date.now() - 3_600_000).toISOString(),
           participants: PREVIEW_PARTICIPANTS.slice(0, 4).map(
             (participant, index) => ({
               ...participant,
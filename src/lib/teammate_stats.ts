@@ -0,0 +1,51 @@
+import { invoke } from "@tauri-apps/api/tauri";
+import { formatAssignedPosition } from "$lib/champ_select";
+
+export interface TeammateStats {
+  puuid: string;
+  rank: Rank | null;
+  games: number;
+  wins: number;
+  /** Rounded percentage, null without games. */
+  winRate: number | null;
+  topChampions: ChampionPlays[];
+  mainRole: string | null;
+}
+
+export interface Rank {
+  queue: string;
+  tier: string;
+  division: string;
+  leaguePoints: number;
+  wins: number;
+  losses: number;
+}
+
+export interface ChampionPlays {
+  championId: number;
+  games: number;
+  wins: number;
+}
+
+/** Results arrive one player at a time as `teammate_stats` events. */
+export async function requestTeammateStats(puuids: string[]): Promise<void> {
+  await invoke<void>("get_teammate_stats", { puuids });
+}
+
+const APEX_TIERS = ["MASTER", "GRANDMASTER", "CHALLENGER"];
+
+export function formatRank(rank: Rank | null): string {
+  if (!rank) return "Unranked";
+  const tier = `${rank.tier.slice(0, 1)}${rank.tier.slice(1).toLowerCase()}`;
+  if (APEX_TIERS.includes(rank.tier)) return `${tier} ${rank.leaguePoints} LP`;
+  return `${tier} ${rank.division}`;
+}
+
+export function formatTeammateStats(stats: TeammateStats): string {
+  const parts = [formatRank(stats.rank)];
+  if (stats.winRate !== null) {
+    parts.push(`${stats.winRate}% WR (${stats.games} games)`);
+  }
+  if (stats.mainRole) parts.push(formatAssignedPosition(stats.mainRole));
+  return parts.join(" · ");
+}